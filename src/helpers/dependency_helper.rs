@@ -0,0 +1,160 @@
+use mame_parser::models::Machine;
+use std::collections::{HashMap, VecDeque};
+
+/**
+ * Machine needed by the current machines that is not in the current data set.
+ */
+pub struct MissingDependency {
+    pub name: String,
+    /**
+     * How the machine is needed: parent, BIOS or device.
+     */
+    pub kind: &'static str,
+    pub required_by: Vec<String>,
+    /**
+     * Whether the machine was removed by a filter and can be added back.
+     */
+    pub is_available: bool,
+}
+
+/**
+ * Get the parents (`clone_of`), BIOS (`rom_of`) and devices (`device_refs`) of a machine.
+ */
+pub fn get_dependencies(machine: &Machine) -> Vec<(&str, &'static str)> {
+    let mut dependencies = Vec::new();
+
+    if let Some(parent) = &machine.clone_of {
+        dependencies.push((parent.as_str(), "parent"));
+    }
+    if let Some(rom_of) = &machine.rom_of {
+        if machine.clone_of.as_ref() != Some(rom_of) {
+            dependencies.push((rom_of.as_str(), "BIOS"));
+        }
+    }
+    for device_ref in &machine.device_refs {
+        dependencies.push((device_ref.name.as_str(), "device"));
+    }
+
+    dependencies
+}
+
+/**
+ * Get every machine needed by the current machines that is not in the current data set.
+ * The dependencies of the removed machines needed are followed too, so adding back
+ * all the available ones leaves a complete set.
+ */
+pub fn get_missing_dependencies(
+    machines: &HashMap<String, Machine>,
+    removed_machines: &HashMap<&str, &Machine>,
+) -> Vec<MissingDependency> {
+    let mut missing: HashMap<String, MissingDependency> = HashMap::new();
+    let mut pending: VecDeque<&Machine> = machines.values().collect();
+
+    while let Some(machine) = pending.pop_front() {
+        for (name, kind) in get_dependencies(machine) {
+            if machines.contains_key(name) {
+                continue;
+            }
+
+            if let Some(dependency) = missing.get_mut(name) {
+                if !dependency.required_by.contains(&machine.name) {
+                    dependency.required_by.push(machine.name.clone());
+                }
+                continue;
+            }
+
+            let removed_machine = removed_machines.get(name);
+            if let Some(removed_machine) = removed_machine {
+                pending.push_back(removed_machine);
+            }
+            missing.insert(
+                name.to_string(),
+                MissingDependency {
+                    name: name.to_string(),
+                    kind,
+                    required_by: vec![machine.name.clone()],
+                    is_available: removed_machine.is_some(),
+                },
+            );
+        }
+    }
+
+    let mut missing: Vec<MissingDependency> = missing.into_values().collect();
+    for dependency in missing.iter_mut() {
+        dependency.required_by.sort();
+    }
+    missing.sort_by(|a, b| a.kind.cmp(b.kind).then(a.name.cmp(&b.name)));
+
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mame_parser::models::DeviceRef;
+
+    fn machine(
+        name: &str,
+        clone_of: Option<&str>,
+        rom_of: Option<&str>,
+        devices: &[&str],
+    ) -> Machine {
+        let mut machine = Machine::new(name.to_string());
+        machine.clone_of = clone_of.map(str::to_string);
+        machine.rom_of = rom_of.map(str::to_string);
+        machine.device_refs = devices
+            .iter()
+            .map(|device| DeviceRef {
+                name: device.to_string(),
+            })
+            .collect();
+        machine
+    }
+
+    #[test]
+    fn gets_the_parent_once_for_clones() {
+        let clone = machine("mslugb", Some("mslug"), Some("mslug"), &["ym2610"]);
+        assert_eq!(
+            get_dependencies(&clone),
+            [("mslug", "parent"), ("ym2610", "device")]
+        );
+    }
+
+    #[test]
+    fn gets_the_bios_of_parents() {
+        let parent = machine("mslug", None, Some("neogeo"), &[]);
+        assert_eq!(get_dependencies(&parent), [("neogeo", "BIOS")]);
+    }
+
+    #[test]
+    fn follows_the_dependencies_of_removed_machines() {
+        let machines: HashMap<String, Machine> =
+            [machine("mslugb", Some("mslug"), Some("mslug"), &[])]
+                .into_iter()
+                .map(|machine| (machine.name.clone(), machine))
+                .collect();
+        let parent = machine("mslug", None, Some("neogeo"), &[]);
+        let removed_machines: HashMap<&str, &Machine> = [("mslug", &parent)].into_iter().collect();
+
+        let missing = get_missing_dependencies(&machines, &removed_machines);
+
+        let missing: Vec<(&str, &str, &[String], bool)> = missing
+            .iter()
+            .map(|dependency| {
+                (
+                    dependency.name.as_str(),
+                    dependency.kind,
+                    dependency.required_by.as_slice(),
+                    dependency.is_available,
+                )
+            })
+            .collect();
+        assert_eq!(
+            missing,
+            [
+                ("neogeo", "BIOS", &["mslug".to_string()][..], false),
+                ("mslug", "parent", &["mslugb".to_string()][..], true),
+            ]
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use crate::models::machine_details::{Display, MachineDetails};
+use mame_parser::models::Machine;
+use std::collections::{HashMap, HashSet};
+
+/**
+ * Get the control types of a machine.
+ */
+pub fn get_control_types(details: Option<&MachineDetails>) -> HashSet<String> {
+    details
+        .and_then(|details| details.input.as_ref())
+        .map(|input| {
+            input
+                .controls
+                .iter()
+                .map(|control| control.type_.clone())
+                .filter(|type_| !type_.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/**
+ * Get the control types with the number of machines using each one.
+ */
+pub fn get_control_types_list(
+    machines: &HashMap<String, Machine>,
+    machines_details: &HashMap<String, MachineDetails>,
+) -> HashMap<String, usize> {
+    let mut control_types: HashMap<String, usize> = HashMap::new();
+
+    for name in machines.keys() {
+        for type_ in get_control_types(machines_details.get(name)) {
+            *control_types.entry(type_).or_insert(0) += 1;
+        }
+    }
+
+    control_types
+}
+
+/**
+ * Get the CPUs with the number of machines using each one.
+ */
+pub fn get_cpus_list(
+    machines: &HashMap<String, Machine>,
+    machines_details: &HashMap<String, MachineDetails>,
+) -> HashMap<String, usize> {
+    let mut cpus: HashMap<String, usize> = HashMap::new();
+
+    for name in machines.keys() {
+        if let Some(details) = machines_details.get(name) {
+            let machine_cpus: HashSet<&String> = details
+                .chips
+                .iter()
+                .filter(|chip| chip.type_ == "cpu")
+                .map(|chip| &chip.name)
+                .collect();
+            for cpu in machine_cpus {
+                *cpus.entry(cpu.clone()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    cpus
+}
+
+/**
+ * Check if a display is rotated to a vertical orientation.
+ */
+pub fn is_vertical(display: &Display) -> bool {
+    display.rotate == 90 || display.rotate == 270
+}
+
+/**
+ * Get the orientation of the main display of a machine.
+ */
+pub fn get_orientation(details: Option<&MachineDetails>) -> Option<&'static str> {
+    details
+        .and_then(|details| details.displays.first())
+        .map(|display| {
+            if is_vertical(display) {
+                "Vertical"
+            } else {
+                "Horizontal"
+            }
+        })
+}
+
+/**
+ * Get the emulation issues of a machine as "<type> <status>", like "sound imperfect".
+ */
+pub fn get_feature_issues(details: Option<&MachineDetails>) -> HashSet<String> {
+    details
+        .map(|details| {
+            details
+                .features
+                .iter()
+                .filter_map(|feature| {
+                    feature
+                        .status
+                        .as_ref()
+                        .or(feature.overall.as_ref())
+                        .map(|status| format!("{} {}", feature.type_, status))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/**
+ * Get the emulation issues with the number of machines having each one.
+ */
+pub fn get_feature_issues_list(
+    machines: &HashMap<String, Machine>,
+    machines_details: &HashMap<String, MachineDetails>,
+) -> HashMap<String, usize> {
+    let mut issues: HashMap<String, usize> = HashMap::new();
+
+    for name in machines.keys() {
+        for issue in get_feature_issues(machines_details.get(name)) {
+            *issues.entry(issue).or_insert(0) += 1;
+        }
+    }
+
+    issues
+}
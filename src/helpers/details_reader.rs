@@ -0,0 +1,242 @@
+use crate::models::machine_details::{
+    Chip, Control, DipSwitch, DipValue, Display, Feature, Input, MachineDetails, Sound,
+};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use regex::Regex;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/**
+ * Find the MAME XML file in the extracted mame folder of the workspace.
+ * A `.xml` file (for example the output of `mame -listxml`) is preferred over the downloaded dat file.
+ */
+pub fn find_mame_xml_file(workspace_path: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    let mame_folder = workspace_path.join("extracted").join("mame");
+    let dat_pattern = Regex::new(r"MAME\s+[0-9]*\.[0-9]+\.dat").unwrap();
+
+    let files: Vec<PathBuf> = WalkDir::new(&mame_folder)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_file())
+        .map(|e| e.path().to_path_buf())
+        .collect();
+
+    let xml_file = files.iter().find(|path| {
+        path.extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("xml"))
+    });
+    let dat_file = files.iter().find(|path| {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| dat_pattern.is_match(name))
+    });
+
+    match xml_file.or(dat_file) {
+        Some(path) => Ok(path.clone()),
+        None => Err(format!("No MAME XML file found in {}", mame_folder.display()).into()),
+    }
+}
+
+/**
+ * Read the inputs, displays, chips, sound, DIP switches and features of every machine in the MAME XML file.
+ */
+pub fn read_machine_details(
+    file_path: &Path,
+) -> Result<HashMap<String, MachineDetails>, Box<dyn Error>> {
+    let mut machines_details: HashMap<String, MachineDetails> = HashMap::new();
+
+    let mut xml_reader = Reader::from_reader(BufReader::new(File::open(file_path)?));
+    xml_reader.trim_text(true);
+
+    let mut buf = Vec::with_capacity(8 * 1024);
+
+    let mut current_machine: Option<(String, MachineDetails)> = None;
+    let mut current_dipswitch: Option<DipSwitch> = None;
+
+    loop {
+        match xml_reader.read_event(&mut buf) {
+            Ok(Event::Start(ref e)) => {
+                if e.name() == b"dipswitch" {
+                    current_dipswitch = Some(read_dipswitch(e, &xml_reader)?);
+                } else {
+                    process_node(e, &xml_reader, &mut current_machine, &mut current_dipswitch)?;
+                }
+            }
+            Ok(Event::Empty(ref e)) => {
+                process_node(e, &xml_reader, &mut current_machine, &mut current_dipswitch)?;
+            }
+            Ok(Event::End(ref e)) => match e.name() {
+                b"dipswitch" => {
+                    if let (Some((_, details)), Some(dipswitch)) =
+                        (current_machine.as_mut(), current_dipswitch.take())
+                    {
+                        details.dipswitches.push(dipswitch);
+                    }
+                }
+                b"machine" | b"game" => {
+                    if let Some((name, details)) = current_machine.take() {
+                        if !details.is_empty() {
+                            machines_details.insert(name, details);
+                        }
+                    }
+                }
+                _ => (),
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(Box::new(e)),
+            _ => (),
+        }
+        buf.clear();
+    }
+
+    Ok(machines_details)
+}
+
+/**
+ * Process an XML node adding its information to the current machine.
+ */
+fn process_node(
+    e: &BytesStart,
+    reader: &Reader<BufReader<File>>,
+    current_machine: &mut Option<(String, MachineDetails)>,
+    current_dipswitch: &mut Option<DipSwitch>,
+) -> Result<(), Box<dyn Error>> {
+    if matches!(e.name(), b"machine" | b"game") {
+        let attributes = get_attributes(e, reader)?;
+        if let Some(name) = attributes.get("name") {
+            *current_machine = Some((name.clone(), MachineDetails::default()));
+        }
+        return Ok(());
+    }
+
+    let details = match current_machine {
+        Some((_, details)) => details,
+        None => return Ok(()),
+    };
+
+    match e.name() {
+        b"input" => {
+            let attributes = get_attributes(e, reader)?;
+            details.input = Some(Input {
+                players: parse_attribute(&attributes, "players").unwrap_or(0),
+                coins: parse_attribute(&attributes, "coins"),
+                service: attributes.get("service").is_some_and(|v| v == "yes"),
+                tilt: attributes.get("tilt").is_some_and(|v| v == "yes"),
+                controls: Vec::new(),
+            });
+        }
+        b"control" => {
+            let attributes = get_attributes(e, reader)?;
+            if let Some(input) = details.input.as_mut() {
+                input.controls.push(Control {
+                    type_: attributes.get("type").cloned().unwrap_or_default(),
+                    player: parse_attribute(&attributes, "player"),
+                    buttons: parse_attribute(&attributes, "buttons"),
+                    req_buttons: parse_attribute(&attributes, "reqbuttons"),
+                    ways: attributes.get("ways").cloned(),
+                });
+            }
+        }
+        b"display" => {
+            let attributes = get_attributes(e, reader)?;
+            details.displays.push(Display {
+                tag: attributes.get("tag").cloned(),
+                type_: attributes.get("type").cloned().unwrap_or_default(),
+                rotate: parse_attribute(&attributes, "rotate").unwrap_or(0),
+                flipx: attributes.get("flipx").is_some_and(|v| v == "yes"),
+                width: parse_attribute(&attributes, "width"),
+                height: parse_attribute(&attributes, "height"),
+                refresh: parse_attribute(&attributes, "refresh"),
+            });
+        }
+        b"chip" => {
+            let attributes = get_attributes(e, reader)?;
+            details.chips.push(Chip {
+                type_: attributes.get("type").cloned().unwrap_or_default(),
+                tag: attributes.get("tag").cloned(),
+                name: attributes.get("name").cloned().unwrap_or_default(),
+                clock: parse_attribute(&attributes, "clock"),
+            });
+        }
+        b"sound" => {
+            let attributes = get_attributes(e, reader)?;
+            details.sound = Some(Sound {
+                channels: parse_attribute(&attributes, "channels").unwrap_or(0),
+            });
+        }
+        b"dipswitch" => {
+            // Dip switch without values
+            details.dipswitches.push(read_dipswitch(e, reader)?);
+        }
+        b"dipvalue" => {
+            let attributes = get_attributes(e, reader)?;
+            if let Some(dipswitch) = current_dipswitch.as_mut() {
+                dipswitch.values.push(DipValue {
+                    name: attributes.get("name").cloned().unwrap_or_default(),
+                    value: attributes.get("value").cloned(),
+                    is_default: attributes.get("default").is_some_and(|v| v == "yes"),
+                });
+            }
+        }
+        b"feature" => {
+            let attributes = get_attributes(e, reader)?;
+            details.features.push(Feature {
+                type_: attributes.get("type").cloned().unwrap_or_default(),
+                status: attributes.get("status").cloned(),
+                overall: attributes.get("overall").cloned(),
+            });
+        }
+        _ => (),
+    }
+
+    Ok(())
+}
+
+/**
+ * Read a dip switch node without its values.
+ */
+fn read_dipswitch(
+    e: &BytesStart,
+    reader: &Reader<BufReader<File>>,
+) -> Result<DipSwitch, Box<dyn Error>> {
+    let attributes = get_attributes(e, reader)?;
+    Ok(DipSwitch {
+        name: attributes.get("name").cloned().unwrap_or_default(),
+        tag: attributes.get("tag").cloned(),
+        mask: attributes.get("mask").cloned(),
+        values: Vec::new(),
+    })
+}
+
+/**
+ * Get the attributes of a node as a map.
+ */
+fn get_attributes(
+    e: &BytesStart,
+    reader: &Reader<BufReader<File>>,
+) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    let mut attributes = HashMap::new();
+    for attr in e.attributes() {
+        let attr = attr?;
+        attributes.insert(
+            String::from_utf8_lossy(attr.key).into_owned(),
+            attr.unescape_and_decode_value(reader)?,
+        );
+    }
+    Ok(attributes)
+}
+
+/**
+ * Parse the value of an attribute.
+ */
+fn parse_attribute<T: std::str::FromStr>(
+    attributes: &HashMap<String, String>,
+    name: &str,
+) -> Option<T> {
+    attributes.get(name)?.parse().ok()
+}
@@ -0,0 +1,625 @@
+use crate::helpers::dependency_helper::MissingDependency;
+use crate::helpers::players_helper::get_player_count;
+use crate::models::machine_details::MachineDetails;
+use crate::models::player_count::PlayerCount;
+use mame_parser::models::{ExportFileType, Machine};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, ErrorKind};
+use std::path::Path;
+
+/**
+ * Add the machine details and the parsed player counts to the files exported by mame-parser.
+ * SQLite gets new tables, JSON gets new fields in machines.json and CSV gets new files.
+ */
+pub fn write_machine_details(
+    export_file_type: ExportFileType,
+    export_folder: &Path,
+    machines: &HashMap<String, Machine>,
+    machines_details: &HashMap<String, MachineDetails>,
+) -> Result<(), Box<dyn Error>> {
+    let mut details: Vec<(&String, &MachineDetails)> = machines_details
+        .iter()
+        .filter(|(name, _)| machines.contains_key(*name))
+        .collect();
+    details.sort_by_key(|&(name, _)| name);
+
+    let mut player_counts: Vec<(&String, PlayerCount)> = machines
+        .iter()
+        .filter_map(|(name, machine)| get_player_count(machine).map(|count| (name, count)))
+        .collect();
+    player_counts.sort_by_key(|(name, _)| *name);
+
+    match export_file_type {
+        ExportFileType::Sqlite => {
+            let data_base_path = export_folder.join("machines.db");
+            write_sqlite(&data_base_path, &details)?;
+            write_sqlite_player_counts(&data_base_path, &player_counts)
+        }
+        ExportFileType::Json => write_json(
+            &export_folder.join("machines.json"),
+            machines,
+            machines_details,
+        ),
+        ExportFileType::Csv => {
+            write_csv(export_folder, &details)?;
+            write_csv_player_counts(export_folder, &player_counts)
+        }
+    }
+}
+
+/**
+ * Create the details tables in the SQLite database and insert the details.
+ */
+fn write_sqlite(
+    data_base_path: &Path,
+    details: &[(&String, &MachineDetails)],
+) -> Result<(), Box<dyn Error>> {
+    let mut conn = Connection::open(data_base_path)?;
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS inputs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            machine_name TEXT,
+            players INTEGER,
+            coins INTEGER,
+            service INTEGER,
+            tilt INTEGER,
+            machine_id INTEGER,
+            FOREIGN KEY(machine_id) REFERENCES machines(id)
+        );
+        CREATE TABLE IF NOT EXISTS controls (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            machine_name TEXT,
+            type TEXT,
+            player INTEGER,
+            buttons INTEGER,
+            req_buttons INTEGER,
+            ways TEXT,
+            machine_id INTEGER,
+            FOREIGN KEY(machine_id) REFERENCES machines(id)
+        );
+        CREATE TABLE IF NOT EXISTS displays (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            machine_name TEXT,
+            tag TEXT,
+            type TEXT,
+            rotate INTEGER,
+            flipx INTEGER,
+            width INTEGER,
+            height INTEGER,
+            refresh REAL,
+            machine_id INTEGER,
+            FOREIGN KEY(machine_id) REFERENCES machines(id)
+        );
+        CREATE TABLE IF NOT EXISTS chips (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            machine_name TEXT,
+            type TEXT,
+            tag TEXT,
+            name TEXT,
+            clock INTEGER,
+            machine_id INTEGER,
+            FOREIGN KEY(machine_id) REFERENCES machines(id)
+        );
+        CREATE TABLE IF NOT EXISTS sounds (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            machine_name TEXT,
+            channels INTEGER,
+            machine_id INTEGER,
+            FOREIGN KEY(machine_id) REFERENCES machines(id)
+        );
+        CREATE TABLE IF NOT EXISTS dipswitches (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            machine_name TEXT,
+            name TEXT,
+            tag TEXT,
+            mask TEXT,
+            machine_id INTEGER,
+            FOREIGN KEY(machine_id) REFERENCES machines(id)
+        );
+        CREATE TABLE IF NOT EXISTS dipswitch_values (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            dipswitch_id INTEGER,
+            name TEXT,
+            value TEXT,
+            is_default INTEGER,
+            FOREIGN KEY(dipswitch_id) REFERENCES dipswitches(id)
+        );
+        CREATE TABLE IF NOT EXISTS features (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            machine_name TEXT,
+            type TEXT,
+            status TEXT,
+            overall TEXT,
+            machine_id INTEGER,
+            FOREIGN KEY(machine_id) REFERENCES machines(id)
+        );",
+    )?;
+
+    let transaction = conn.transaction()?;
+    {
+        for (name, machine_details) in details {
+            let machine_id: Option<i64> = transaction
+                .query_row(
+                    "SELECT id FROM machines WHERE name = ?1",
+                    params![name],
+                    |row| row.get(0),
+                )
+                .ok();
+
+            if let Some(input) = &machine_details.input {
+                transaction.execute(
+                    "INSERT INTO inputs (machine_name, players, coins, service, tilt, machine_id)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        name,
+                        input.players,
+                        input.coins,
+                        input.service,
+                        input.tilt,
+                        machine_id
+                    ],
+                )?;
+                for control in &input.controls {
+                    transaction.execute(
+                        "INSERT INTO controls (machine_name, type, player, buttons, req_buttons, ways, machine_id)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        params![
+                            name,
+                            control.type_,
+                            control.player,
+                            control.buttons,
+                            control.req_buttons,
+                            control.ways,
+                            machine_id
+                        ],
+                    )?;
+                }
+            }
+
+            for display in &machine_details.displays {
+                transaction.execute(
+                    "INSERT INTO displays (machine_name, tag, type, rotate, flipx, width, height, refresh, machine_id)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        name,
+                        display.tag,
+                        display.type_,
+                        display.rotate,
+                        display.flipx,
+                        display.width,
+                        display.height,
+                        display.refresh,
+                        machine_id
+                    ],
+                )?;
+            }
+
+            for chip in &machine_details.chips {
+                transaction.execute(
+                    "INSERT INTO chips (machine_name, type, tag, name, clock, machine_id)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        name,
+                        chip.type_,
+                        chip.tag,
+                        chip.name,
+                        chip.clock.map(|clock| clock as i64),
+                        machine_id
+                    ],
+                )?;
+            }
+
+            if let Some(sound) = &machine_details.sound {
+                transaction.execute(
+                    "INSERT INTO sounds (machine_name, channels, machine_id) VALUES (?1, ?2, ?3)",
+                    params![name, sound.channels, machine_id],
+                )?;
+            }
+
+            for dipswitch in &machine_details.dipswitches {
+                transaction.execute(
+                    "INSERT INTO dipswitches (machine_name, name, tag, mask, machine_id)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        name,
+                        dipswitch.name,
+                        dipswitch.tag,
+                        dipswitch.mask,
+                        machine_id
+                    ],
+                )?;
+                let dipswitch_id = transaction.last_insert_rowid();
+                for value in &dipswitch.values {
+                    transaction.execute(
+                        "INSERT INTO dipswitch_values (dipswitch_id, name, value, is_default)
+                         VALUES (?1, ?2, ?3, ?4)",
+                        params![dipswitch_id, value.name, value.value, value.is_default],
+                    )?;
+                }
+            }
+
+            for feature in &machine_details.features {
+                transaction.execute(
+                    "INSERT INTO features (machine_name, type, status, overall, machine_id)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        name,
+                        feature.type_,
+                        feature.status,
+                        feature.overall,
+                        machine_id
+                    ],
+                )?;
+            }
+        }
+    }
+    transaction.commit()?;
+
+    Ok(())
+}
+
+/**
+ * Create the player counts table in the SQLite database and insert the player counts.
+ */
+fn write_sqlite_player_counts(
+    data_base_path: &Path,
+    player_counts: &[(&String, PlayerCount)],
+) -> Result<(), Box<dyn Error>> {
+    let mut conn = Connection::open(data_base_path)?;
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS player_counts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            machine_name TEXT,
+            max_players INTEGER,
+            max_simultaneous INTEGER,
+            simultaneous INTEGER,
+            alternating INTEGER,
+            coop INTEGER,
+            versus INTEGER,
+            machine_id INTEGER,
+            FOREIGN KEY(machine_id) REFERENCES machines(id)
+        );",
+    )?;
+
+    let transaction = conn.transaction()?;
+    for (name, player_count) in player_counts {
+        transaction.execute(
+            "INSERT INTO player_counts (machine_name, max_players, max_simultaneous, simultaneous, alternating, coop, versus, machine_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, (SELECT id FROM machines WHERE name = ?1))",
+            params![
+                name,
+                player_count.max_players,
+                player_count.max_simultaneous,
+                player_count.simultaneous,
+                player_count.alternating,
+                player_count.coop,
+                player_count.versus
+            ],
+        )?;
+    }
+    transaction.commit()?;
+
+    Ok(())
+}
+
+/**
+ * Add the details and the player count as new fields of each machine in machines.json.
+ */
+fn write_json(
+    machines_file: &Path,
+    machines_data: &HashMap<String, Machine>,
+    machines_details: &HashMap<String, MachineDetails>,
+) -> Result<(), Box<dyn Error>> {
+    let mut machines: Vec<Value> =
+        serde_json::from_reader(BufReader::new(File::open(machines_file)?))?;
+
+    let empty_details = MachineDetails::default();
+    for machine in machines.iter_mut() {
+        let name = machine["name"].as_str().unwrap_or_default().to_string();
+        let details = machines_details.get(&name).unwrap_or(&empty_details);
+        let player_count = machines_data.get(&name).and_then(get_player_count);
+
+        if let Value::Object(fields) = machine {
+            fields.insert(
+                "player_count".to_string(),
+                serde_json::to_value(&player_count)?,
+            );
+            fields.insert("input".to_string(), serde_json::to_value(&details.input)?);
+            fields.insert(
+                "displays".to_string(),
+                serde_json::to_value(&details.displays)?,
+            );
+            fields.insert("chips".to_string(), serde_json::to_value(&details.chips)?);
+            fields.insert("sound".to_string(), serde_json::to_value(&details.sound)?);
+            fields.insert(
+                "dipswitches".to_string(),
+                serde_json::to_value(&details.dipswitches)?,
+            );
+            fields.insert(
+                "features".to_string(),
+                serde_json::to_value(&details.features)?,
+            );
+        }
+    }
+
+    serde_json::to_writer_pretty(BufWriter::new(File::create(machines_file)?), &machines)?;
+
+    Ok(())
+}
+
+/**
+ * Write the details to new CSV files.
+ */
+fn write_csv(
+    export_folder: &Path,
+    details: &[(&String, &MachineDetails)],
+) -> Result<(), Box<dyn Error>> {
+    let mut inputs_wtr = csv::Writer::from_path(export_folder.join("inputs.csv"))?;
+    let mut controls_wtr = csv::Writer::from_path(export_folder.join("controls.csv"))?;
+    let mut displays_wtr = csv::Writer::from_path(export_folder.join("displays.csv"))?;
+    let mut chips_wtr = csv::Writer::from_path(export_folder.join("chips.csv"))?;
+    let mut sounds_wtr = csv::Writer::from_path(export_folder.join("sounds.csv"))?;
+    let mut dipswitches_wtr = csv::Writer::from_path(export_folder.join("dipswitches.csv"))?;
+    let mut features_wtr = csv::Writer::from_path(export_folder.join("features.csv"))?;
+
+    inputs_wtr.write_record(["machine_name", "players", "coins", "service", "tilt"])?;
+    controls_wtr.write_record([
+        "machine_name",
+        "type",
+        "player",
+        "buttons",
+        "req_buttons",
+        "ways",
+    ])?;
+    displays_wtr.write_record([
+        "machine_name",
+        "tag",
+        "type",
+        "rotate",
+        "flipx",
+        "width",
+        "height",
+        "refresh",
+    ])?;
+    chips_wtr.write_record(["machine_name", "type", "tag", "name", "clock"])?;
+    sounds_wtr.write_record(["machine_name", "channels"])?;
+    dipswitches_wtr.write_record([
+        "machine_name",
+        "dipswitch_name",
+        "tag",
+        "mask",
+        "value_name",
+        "value",
+        "is_default",
+    ])?;
+    features_wtr.write_record(["machine_name", "type", "status", "overall"])?;
+
+    for (name, machine_details) in details {
+        if let Some(input) = &machine_details.input {
+            inputs_wtr.write_record([
+                name.as_str(),
+                &input.players.to_string(),
+                &to_csv_value(&input.coins),
+                &input.service.to_string(),
+                &input.tilt.to_string(),
+            ])?;
+            for control in &input.controls {
+                controls_wtr.write_record([
+                    name.as_str(),
+                    &control.type_,
+                    &to_csv_value(&control.player),
+                    &to_csv_value(&control.buttons),
+                    &to_csv_value(&control.req_buttons),
+                    &to_csv_value(&control.ways),
+                ])?;
+            }
+        }
+
+        for display in &machine_details.displays {
+            displays_wtr.write_record([
+                name.as_str(),
+                &to_csv_value(&display.tag),
+                &display.type_,
+                &display.rotate.to_string(),
+                &display.flipx.to_string(),
+                &to_csv_value(&display.width),
+                &to_csv_value(&display.height),
+                &to_csv_value(&display.refresh),
+            ])?;
+        }
+
+        for chip in &machine_details.chips {
+            chips_wtr.write_record([
+                name.as_str(),
+                &chip.type_,
+                &to_csv_value(&chip.tag),
+                &chip.name,
+                &to_csv_value(&chip.clock),
+            ])?;
+        }
+
+        if let Some(sound) = &machine_details.sound {
+            sounds_wtr.write_record([name.as_str(), &sound.channels.to_string()])?;
+        }
+
+        // One row per dip switch value
+        for dipswitch in &machine_details.dipswitches {
+            for value in &dipswitch.values {
+                dipswitches_wtr.write_record([
+                    name.as_str(),
+                    &dipswitch.name,
+                    &to_csv_value(&dipswitch.tag),
+                    &to_csv_value(&dipswitch.mask),
+                    &value.name,
+                    &to_csv_value(&value.value),
+                    &value.is_default.to_string(),
+                ])?;
+            }
+        }
+
+        for feature in &machine_details.features {
+            features_wtr.write_record([
+                name.as_str(),
+                &feature.type_,
+                &to_csv_value(&feature.status),
+                &to_csv_value(&feature.overall),
+            ])?;
+        }
+    }
+
+    inputs_wtr.flush()?;
+    controls_wtr.flush()?;
+    displays_wtr.flush()?;
+    chips_wtr.flush()?;
+    sounds_wtr.flush()?;
+    dipswitches_wtr.flush()?;
+    features_wtr.flush()?;
+
+    Ok(())
+}
+
+/**
+ * Write the player counts to a new CSV file.
+ */
+fn write_csv_player_counts(
+    export_folder: &Path,
+    player_counts: &[(&String, PlayerCount)],
+) -> Result<(), Box<dyn Error>> {
+    let mut wtr = csv::Writer::from_path(export_folder.join("player_counts.csv"))?;
+
+    wtr.write_record([
+        "machine_name",
+        "max_players",
+        "max_simultaneous",
+        "simultaneous",
+        "alternating",
+        "coop",
+        "versus",
+    ])?;
+    for (name, player_count) in player_counts {
+        wtr.write_record([
+            name.as_str(),
+            &player_count.max_players.to_string(),
+            &player_count.max_simultaneous.to_string(),
+            &player_count.simultaneous.to_string(),
+            &player_count.alternating.to_string(),
+            &player_count.coop.to_string(),
+            &player_count.versus.to_string(),
+        ])?;
+    }
+    wtr.flush()?;
+
+    Ok(())
+}
+
+/**
+ * Required machine that is not in the exported set, as written to the exported files.
+ */
+#[derive(Serialize)]
+struct RequiredMachineRecord<'a> {
+    name: &'a str,
+    kind: &'a str,
+    required_by: String,
+    is_available: bool,
+}
+
+/**
+ * Flag the machines required by the exported set but not included in it.
+ * SQLite gets a `required_machines` table, JSON and CSV get a `required_machines` file.
+ */
+pub fn write_required_machines(
+    export_file_type: ExportFileType,
+    export_folder: &Path,
+    dependencies: &[MissingDependency],
+) -> Result<(), Box<dyn Error>> {
+    let records: Vec<RequiredMachineRecord> = dependencies
+        .iter()
+        .map(|dependency| RequiredMachineRecord {
+            name: &dependency.name,
+            kind: dependency.kind,
+            required_by: dependency.required_by.join(", "),
+            is_available: dependency.is_available,
+        })
+        .collect();
+
+    match export_file_type {
+        ExportFileType::Sqlite => {
+            let mut conn = Connection::open(export_folder.join("machines.db"))?;
+            conn.execute_batch(
+                "DROP TABLE IF EXISTS required_machines;
+                CREATE TABLE required_machines (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT,
+                    kind TEXT,
+                    required_by TEXT,
+                    is_available INTEGER
+                );",
+            )?;
+            let transaction = conn.transaction()?;
+            for record in &records {
+                transaction.execute(
+                    "INSERT INTO required_machines (name, kind, required_by, is_available)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![
+                        record.name,
+                        record.kind,
+                        record.required_by,
+                        record.is_available
+                    ],
+                )?;
+            }
+            transaction.commit()?;
+        }
+        ExportFileType::Json => {
+            let file = File::create(export_folder.join("required_machines.json"))?;
+            serde_json::to_writer_pretty(BufWriter::new(file), &records)?;
+        }
+        ExportFileType::Csv => {
+            let mut wtr = csv::Writer::from_path(export_folder.join("required_machines.csv"))?;
+            for record in &records {
+                wtr.serialize(record)?;
+            }
+            wtr.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+/**
+ * Remove the machines required by a previous export, so an export without them doesn't keep
+ * the ones of an earlier run.
+ */
+pub fn remove_required_machines(
+    export_file_type: ExportFileType,
+    export_folder: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let file_name = match export_file_type {
+        ExportFileType::Sqlite => {
+            let conn = Connection::open(export_folder.join("machines.db"))?;
+            conn.execute_batch("DROP TABLE IF EXISTS required_machines;")?;
+            return Ok(());
+        }
+        ExportFileType::Json => "required_machines.json",
+        ExportFileType::Csv => "required_machines.csv",
+    };
+
+    match fs::remove_file(export_folder.join(file_name)) {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+/**
+ * Convert an optional value to its CSV representation.
+ */
+fn to_csv_value<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(|v| v.to_string()).unwrap_or_default()
+}
@@ -0,0 +1,623 @@
+use mame_parser::models::Machine;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/**
+ * Fields compared between the two versions of a machine.
+ */
+pub const DIFF_FIELDS: &[&str] = &["driver_status", "description", "year", "category"];
+
+/**
+ * Minimum share of ROMs in common, as a percentage, to consider a machine renamed.
+ */
+pub const MIN_RENAME_CONFIDENCE: f64 = 50.0;
+
+/**
+ * Change found between two data sets, as written to the changelog.
+ */
+#[derive(Debug, Clone, Serialize)]
+pub struct MachineChange {
+    /**
+     * Kind of change: added, removed, renamed, changed, or rom added, rom removed and rom changed.
+     */
+    pub change: &'static str,
+    pub machine: String,
+    pub field: String,
+    pub old_value: String,
+    pub new_value: String,
+}
+
+/**
+ * Differences between two data sets.
+ */
+#[derive(Default)]
+pub struct DataSetDiff {
+    pub added: Vec<MachineChange>,
+    pub removed: Vec<MachineChange>,
+    pub renamed: Vec<MachineChange>,
+    pub changed: Vec<MachineChange>,
+    /**
+     * ROMs added, removed or changed in the machines kept, with the ROM name as field.
+     */
+    pub roms: Vec<MachineChange>,
+}
+
+impl DataSetDiff {
+    /**
+     * Get all the changes in changelog order.
+     */
+    pub fn all_changes(&self) -> Vec<&MachineChange> {
+        self.added
+            .iter()
+            .chain(self.removed.iter())
+            .chain(self.renamed.iter())
+            .chain(self.changed.iter())
+            .chain(self.roms.iter())
+            .collect()
+    }
+
+    /**
+     * Count the changed machines by field.
+     */
+    pub fn count_changed(&self, field: &str) -> usize {
+        self.changed
+            .iter()
+            .filter(|change| change.field == field)
+            .count()
+    }
+
+    /**
+     * Count the machines with added, removed or changed ROMs.
+     */
+    pub fn count_roms_changed(&self) -> usize {
+        self.roms
+            .iter()
+            .map(|change| &change.machine)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+}
+
+/**
+ * Machine found under a different name in the new data set.
+ */
+#[derive(Debug, Clone, Serialize)]
+pub struct RenameMatch {
+    pub old_name: String,
+    pub new_name: String,
+    /**
+     * Percentage of ROMs in common between both machines.
+     */
+    pub confidence: f64,
+}
+
+/**
+ * Compare two data sets.
+ * Machines removed and added sharing most of their ROMs are reported as renamed.
+ */
+pub fn diff_data_sets(
+    old_machines: &HashMap<String, Machine>,
+    new_machines: &HashMap<String, Machine>,
+) -> DataSetDiff {
+    let mut diff = DataSetDiff::default();
+
+    let mut removed: Vec<&Machine> = old_machines
+        .values()
+        .filter(|machine| !new_machines.contains_key(&machine.name))
+        .collect();
+    let mut added: Vec<&Machine> = new_machines
+        .values()
+        .filter(|machine| !old_machines.contains_key(&machine.name))
+        .collect();
+
+    // Renamed machines keep most of their ROMs
+    let renamed = detect_renames(
+        &removed,
+        &added,
+        old_machines,
+        new_machines,
+        MIN_RENAME_CONFIDENCE,
+    );
+    removed.retain(|machine| !renamed.iter().any(|rename| rename.old_name == machine.name));
+    added.retain(|machine| !renamed.iter().any(|rename| rename.new_name == machine.name));
+
+    for machine in added {
+        diff.added.push(MachineChange {
+            change: "added",
+            machine: machine.name.clone(),
+            field: String::new(),
+            old_value: String::new(),
+            new_value: machine.description.clone().unwrap_or_default(),
+        });
+    }
+    for machine in removed {
+        diff.removed.push(MachineChange {
+            change: "removed",
+            machine: machine.name.clone(),
+            field: String::new(),
+            old_value: machine.description.clone().unwrap_or_default(),
+            new_value: String::new(),
+        });
+    }
+    for rename in renamed {
+        diff.renamed.push(MachineChange {
+            change: "renamed",
+            machine: rename.new_name.clone(),
+            field: "name".to_string(),
+            old_value: rename.old_name,
+            new_value: rename.new_name,
+        });
+    }
+
+    for old_machine in old_machines.values() {
+        if let Some(new_machine) = new_machines.get(&old_machine.name) {
+            for field in DIFF_FIELDS {
+                let old_value = get_field_value(old_machine, field);
+                let new_value = get_field_value(new_machine, field);
+                if old_value != new_value {
+                    diff.changed.push(MachineChange {
+                        change: "changed",
+                        machine: old_machine.name.clone(),
+                        field: field.to_string(),
+                        old_value,
+                        new_value,
+                    });
+                }
+            }
+            diff.roms.extend(diff_roms(old_machine, new_machine));
+        }
+    }
+
+    diff.added.sort_by(|a, b| a.machine.cmp(&b.machine));
+    diff.removed.sort_by(|a, b| a.machine.cmp(&b.machine));
+    diff.renamed.sort_by(|a, b| a.machine.cmp(&b.machine));
+    diff.changed
+        .sort_by(|a, b| a.machine.cmp(&b.machine).then(a.field.cmp(&b.field)));
+    diff.roms
+        .sort_by(|a, b| a.machine.cmp(&b.machine).then(a.field.cmp(&b.field)));
+
+    diff
+}
+
+/**
+ * Find the machines of the old data set that exist under a different name in the new one.
+ */
+pub fn find_renamed_machines(
+    old_machines: &HashMap<String, Machine>,
+    new_machines: &HashMap<String, Machine>,
+    min_confidence: f64,
+) -> Vec<RenameMatch> {
+    let removed: Vec<&Machine> = old_machines
+        .values()
+        .filter(|machine| !new_machines.contains_key(&machine.name))
+        .collect();
+    let added: Vec<&Machine> = new_machines
+        .values()
+        .filter(|machine| !old_machines.contains_key(&machine.name))
+        .collect();
+
+    detect_renames(&removed, &added, old_machines, new_machines, min_confidence)
+}
+
+/**
+ * Match the removed machines with the added ones by their own ROM hashes.
+ * The confidence is the percentage of ROMs in common over all the ROMs of both machines,
+ * each machine is matched at most once starting with the highest confidence.
+ */
+fn detect_renames(
+    removed: &[&Machine],
+    added: &[&Machine],
+    old_machines: &HashMap<String, Machine>,
+    new_machines: &HashMap<String, Machine>,
+    min_confidence: f64,
+) -> Vec<RenameMatch> {
+    let added_hashes: Vec<HashSet<String>> = added
+        .iter()
+        .map(|machine| get_own_rom_hashes(machine, new_machines))
+        .collect();
+
+    let mut added_by_hash: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, hashes) in added_hashes.iter().enumerate() {
+        for hash in hashes {
+            added_by_hash.entry(hash.as_str()).or_default().push(index);
+        }
+    }
+
+    let mut candidates: Vec<(usize, usize, f64)> = Vec::new();
+    for (removed_index, machine) in removed.iter().enumerate() {
+        let hashes = get_own_rom_hashes(machine, old_machines);
+        if hashes.is_empty() {
+            continue;
+        }
+
+        let mut shared: HashMap<usize, usize> = HashMap::new();
+        for hash in &hashes {
+            for added_index in added_by_hash.get(hash.as_str()).into_iter().flatten() {
+                *shared.entry(*added_index).or_insert(0) += 1;
+            }
+        }
+
+        for (added_index, count) in shared {
+            let union = hashes.len() + added_hashes[added_index].len() - count;
+            let confidence = (count as f64 * 1000.0 / union as f64).round() / 10.0;
+            if confidence >= min_confidence {
+                candidates.push((removed_index, added_index, confidence));
+            }
+        }
+    }
+
+    candidates.sort_by(|a, b| {
+        b.2.partial_cmp(&a.2)
+            .unwrap()
+            .then(removed[a.0].name.cmp(&removed[b.0].name))
+            .then(added[a.1].name.cmp(&added[b.1].name))
+    });
+
+    let mut matched_removed: HashSet<usize> = HashSet::new();
+    let mut matched_added: HashSet<usize> = HashSet::new();
+    let mut renames = Vec::new();
+    for (removed_index, added_index, confidence) in candidates {
+        if matched_removed.contains(&removed_index) || matched_added.contains(&added_index) {
+            continue;
+        }
+        matched_removed.insert(removed_index);
+        matched_added.insert(added_index);
+        renames.push(RenameMatch {
+            old_name: removed[removed_index].name.clone(),
+            new_name: added[added_index].name.clone(),
+            confidence,
+        });
+    }
+
+    renames.sort_by(|a, b| a.old_name.cmp(&b.old_name));
+
+    renames
+}
+
+/**
+ * Get the value of a compared field as text.
+ */
+fn get_field_value(machine: &Machine, field: &str) -> String {
+    match field {
+        "driver_status" => machine.driver_status.clone().unwrap_or_default(),
+        "description" => machine.description.clone().unwrap_or_default(),
+        "year" => machine.year.clone().unwrap_or_default(),
+        "category" => match (&machine.category, &machine.subcategory) {
+            (Some(category), Some(subcategory)) => format!("{} - {}", category, subcategory),
+            (Some(category), None) => category.clone(),
+            _ => String::new(),
+        },
+        _ => unreachable!(),
+    }
+}
+
+/**
+ * Compare the dumped ROMs of two versions of a machine by name, with one change for each ROM
+ * added, removed or with a different hash.
+ */
+fn diff_roms(old_machine: &Machine, new_machine: &Machine) -> Vec<MachineChange> {
+    let old_roms = get_roms_by_name(old_machine);
+    let new_roms = get_roms_by_name(new_machine);
+
+    let mut changes = Vec::new();
+    for (name, old_hash) in &old_roms {
+        match new_roms.get(name) {
+            None => changes.push(get_rom_change(
+                "rom removed",
+                old_machine,
+                name,
+                old_hash,
+                "",
+            )),
+            Some(new_hash) if new_hash != old_hash => changes.push(get_rom_change(
+                "rom changed",
+                old_machine,
+                name,
+                old_hash,
+                new_hash,
+            )),
+            Some(_) => {}
+        }
+    }
+    for (name, new_hash) in &new_roms {
+        if !old_roms.contains_key(name) {
+            changes.push(get_rom_change("rom added", old_machine, name, "", new_hash));
+        }
+    }
+
+    changes
+}
+
+/**
+ * Get a change of a ROM of a machine.
+ */
+fn get_rom_change(
+    change: &'static str,
+    machine: &Machine,
+    rom_name: &str,
+    old_hash: &str,
+    new_hash: &str,
+) -> MachineChange {
+    MachineChange {
+        change,
+        machine: machine.name.clone(),
+        field: rom_name.to_string(),
+        old_value: old_hash.to_string(),
+        new_value: new_hash.to_string(),
+    }
+}
+
+/**
+ * Get the SHA1, or the CRC when there is no SHA1, of the dumped ROMs of a machine by name.
+ */
+fn get_roms_by_name(machine: &Machine) -> BTreeMap<&str, String> {
+    machine
+        .roms
+        .iter()
+        .filter_map(|rom| {
+            rom.sha1
+                .as_ref()
+                .or(rom.crc.as_ref())
+                .map(|hash| (rom.name.as_str(), hash.to_lowercase()))
+        })
+        .collect()
+}
+
+/**
+ * Get the SHA1, or the CRC when there is no SHA1, of the dumped ROMs of a machine.
+ */
+fn get_rom_hashes(machine: &Machine) -> HashSet<String> {
+    machine
+        .roms
+        .iter()
+        .filter_map(|rom| {
+            rom.sha1
+                .as_ref()
+                .or(rom.crc.as_ref())
+                .map(|hash| hash.to_lowercase())
+        })
+        .collect()
+}
+
+/**
+ * Get the ROM hashes of a machine leaving out the ROMs of its BIOS and devices, which are
+ * shared by every machine using them and would make unrelated machines look alike.
+ */
+fn get_own_rom_hashes(machine: &Machine, machines: &HashMap<String, Machine>) -> HashSet<String> {
+    let mut hashes = get_rom_hashes(machine);
+
+    if let Some(bios) = get_bios(machine, machines) {
+        for hash in get_rom_hashes(bios) {
+            hashes.remove(&hash);
+        }
+    }
+    for device in machine
+        .device_refs
+        .iter()
+        .filter_map(|device_ref| machines.get(&device_ref.name))
+    {
+        for hash in get_rom_hashes(device) {
+            hashes.remove(&hash);
+        }
+    }
+
+    hashes
+}
+
+/**
+ * Get the BIOS of a machine, following `rom_of` through the parent for clones.
+ */
+fn get_bios<'a>(machine: &Machine, machines: &'a HashMap<String, Machine>) -> Option<&'a Machine> {
+    let mut current = machines.get(machine.rom_of.as_deref()?)?;
+    // The chain is at most clone, parent and BIOS, the limit avoids looping on broken data
+    for _ in 0..3 {
+        if current.is_bios == Some(true) {
+            return Some(current);
+        }
+        current = machines.get(current.rom_of.as_deref()?)?;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mame_parser::models::{DeviceRef, Rom};
+
+    fn rom(name: &str, sha1: &str) -> Rom {
+        Rom {
+            name: name.to_string(),
+            size: 1024,
+            merge: None,
+            status: None,
+            crc: None,
+            sha1: Some(sha1.to_string()),
+        }
+    }
+
+    fn machine(name: &str, roms: Vec<Rom>) -> Machine {
+        let mut machine = Machine::new(name.to_string());
+        machine.roms = roms;
+        machine
+    }
+
+    fn get_machines(machines: Vec<Machine>) -> HashMap<String, Machine> {
+        machines
+            .into_iter()
+            .map(|machine| (machine.name.clone(), machine))
+            .collect()
+    }
+
+    #[test]
+    fn lists_rom_changes_by_name() {
+        let old_machines = get_machines(vec![machine(
+            "pacman",
+            vec![rom("a.bin", "aa"), rom("b.bin", "bb"), rom("c.bin", "cc")],
+        )]);
+        let new_machines = get_machines(vec![machine(
+            "pacman",
+            vec![rom("a.bin", "aa"), rom("b.bin", "b2"), rom("d.bin", "dd")],
+        )]);
+
+        let diff = diff_data_sets(&old_machines, &new_machines);
+
+        let changes: Vec<(&str, &str, &str, &str)> = diff
+            .roms
+            .iter()
+            .map(|change| {
+                (
+                    change.change,
+                    change.field.as_str(),
+                    change.old_value.as_str(),
+                    change.new_value.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("rom changed", "b.bin", "bb", "b2"),
+                ("rom removed", "c.bin", "cc", ""),
+                ("rom added", "d.bin", "", "dd"),
+            ]
+        );
+        assert_eq!(diff.count_roms_changed(), 1);
+        assert!(diff.changed.is_empty());
+    }
+
+    fn get_renames(
+        old_machines: &HashMap<String, Machine>,
+        new_machines: &HashMap<String, Machine>,
+    ) -> Vec<(String, String, f64)> {
+        find_renamed_machines(old_machines, new_machines, MIN_RENAME_CONFIDENCE)
+            .into_iter()
+            .map(|rename| (rename.old_name, rename.new_name, rename.confidence))
+            .collect()
+    }
+
+    #[test]
+    fn detects_renames_sharing_most_roms() {
+        let old_machines = get_machines(vec![machine(
+            "pacmanx",
+            vec![
+                rom("a", "aa"),
+                rom("b", "bb"),
+                rom("c", "cc"),
+                rom("d", "dd"),
+            ],
+        )]);
+        let new_machines = get_machines(vec![
+            machine(
+                "pacman2",
+                vec![
+                    rom("a", "aa"),
+                    rom("b", "bb"),
+                    rom("c", "cc"),
+                    rom("e", "ee"),
+                ],
+            ),
+            machine("galaga", vec![rom("g", "gg")]),
+        ]);
+
+        assert_eq!(
+            get_renames(&old_machines, &new_machines),
+            [("pacmanx".to_string(), "pacman2".to_string(), 60.0)]
+        );
+    }
+
+    #[test]
+    fn ignores_machines_sharing_few_roms() {
+        let old_machines = get_machines(vec![machine(
+            "pacmanx",
+            vec![
+                rom("a", "aa"),
+                rom("b", "bb"),
+                rom("c", "cc"),
+                rom("d", "dd"),
+            ],
+        )]);
+        let new_machines = get_machines(vec![machine(
+            "pacman2",
+            vec![
+                rom("a", "aa"),
+                rom("e", "ee"),
+                rom("f", "ff"),
+                rom("g", "gg"),
+            ],
+        )]);
+
+        assert!(get_renames(&old_machines, &new_machines).is_empty());
+    }
+
+    #[test]
+    fn matches_each_machine_once_by_highest_confidence() {
+        let old_machines = get_machines(vec![machine(
+            "pacmanx",
+            vec![
+                rom("a", "aa"),
+                rom("b", "bb"),
+                rom("c", "cc"),
+                rom("d", "dd"),
+            ],
+        )]);
+        let new_machines = get_machines(vec![
+            machine(
+                "pacman2",
+                vec![
+                    rom("a", "aa"),
+                    rom("b", "bb"),
+                    rom("c", "cc"),
+                    rom("d", "dd"),
+                ],
+            ),
+            machine(
+                "pacman3",
+                vec![
+                    rom("a", "aa"),
+                    rom("b", "bb"),
+                    rom("c", "cc"),
+                    rom("e", "ee"),
+                ],
+            ),
+        ]);
+
+        assert_eq!(
+            get_renames(&old_machines, &new_machines),
+            [("pacmanx".to_string(), "pacman2".to_string(), 100.0)]
+        );
+    }
+
+    #[test]
+    fn ignores_bios_and_device_roms() {
+        let mut bios = machine("neogeo", vec![rom("bios1", "b1"), rom("bios2", "b2")]);
+        bios.is_bios = Some(true);
+        let device = machine("ym2610", vec![rom("sound", "s1")]);
+
+        let game = |name: &str, own_rom: Rom| {
+            let mut game = machine(
+                name,
+                vec![
+                    rom("bios1", "b1"),
+                    rom("bios2", "b2"),
+                    rom("sound", "s1"),
+                    own_rom,
+                ],
+            );
+            game.rom_of = Some("neogeo".to_string());
+            game.device_refs = vec![DeviceRef {
+                name: "ym2610".to_string(),
+            }];
+            game
+        };
+
+        let old_machines = get_machines(vec![
+            bios.clone(),
+            device.clone(),
+            game("mslug", rom("p1", "m1")),
+        ]);
+        let new_machines = get_machines(vec![bios, device, game("kof98", rom("p1", "k1"))]);
+
+        assert!(get_renames(&old_machines, &new_machines).is_empty());
+    }
+}
@@ -0,0 +1,48 @@
+/**
+ * Escape the pipes of a value so it doesn't break a Markdown table cell.
+ */
+pub fn escape_markdown(value: &str) -> String {
+    value.replace('|', "\\|")
+}
+
+/**
+ * Escape the characters with a special meaning in XML and HTML.
+ */
+pub fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/**
+ * Escape the backslashes and quotes of a DOT identifier.
+ */
+pub fn escape_dot(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_markdown_pipes() {
+        assert_eq!(escape_markdown("Shoot | Gallery"), "Shoot \\| Gallery");
+    }
+
+    #[test]
+    fn escapes_xml_special_characters() {
+        assert_eq!(
+            escape_xml("<Tom & \"Jerry\">"),
+            "&lt;Tom &amp; &quot;Jerry&quot;&gt;"
+        );
+        assert_eq!(escape_xml("&amp;"), "&amp;amp;");
+    }
+
+    #[test]
+    fn escapes_dot_quotes_and_backslashes() {
+        assert_eq!(escape_dot(r#"a"b\c"#), r#"a\"b\\c"#);
+    }
+}
@@ -0,0 +1,54 @@
+/**
+ * Get a name safe to use in a file name, with every character other than an ASCII letter,
+ * a digit, `-` or `_` replaced by `_`, so it can't point outside of its folder.
+ */
+pub fn get_safe_file_name(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|character| {
+            if character.is_ascii_alphanumeric() || character == '-' || character == '_' {
+                character
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/**
+ * Check that a name has at least a letter or a digit, so its file name is meaningful.
+ */
+pub fn validate_file_name(name: &str) -> Result<(), &'static str> {
+    if name
+        .chars()
+        .any(|character| character.is_ascii_alphanumeric())
+    {
+        Ok(())
+    } else {
+        Err("The name must have at least a letter or a digit")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_letters_digits_dashes_and_underscores() {
+        assert_eq!(get_safe_file_name("Cab-1_v2"), "Cab-1_v2");
+    }
+
+    #[test]
+    fn replaces_spaces_and_path_characters() {
+        assert_eq!(get_safe_file_name(" my cab "), "my_cab");
+        assert_eq!(get_safe_file_name("../etc/passwd"), "___etc_passwd");
+        assert_eq!(get_safe_file_name("C:\\temp"), "C__temp");
+    }
+
+    #[test]
+    fn rejects_names_without_letters_or_digits() {
+        assert!(validate_file_name("..").is_err());
+        assert!(validate_file_name("/ ").is_err());
+        assert!(validate_file_name("cab 1").is_ok());
+    }
+}
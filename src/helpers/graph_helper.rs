@@ -0,0 +1,255 @@
+use crate::helpers::dependency_helper::get_dependencies;
+use crate::helpers::escape_helper::{escape_dot, escape_xml};
+use mame_parser::models::Machine;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/**
+ * Graph of the parents (`clone_of`), BIOS (`rom_of`) and devices (`device_refs`) of the machines.
+ */
+pub struct DependencyGraph {
+    /**
+     * Kind of each node: machine, BIOS, device or missing when it is not in the data set.
+     */
+    pub nodes: BTreeMap<String, &'static str>,
+    /**
+     * Edges from a machine to the machine it depends on, with the kind of dependency.
+     */
+    pub edges: Vec<(String, String, &'static str)>,
+}
+
+/**
+ * Build the dependency graph of all the machines, or only of the machines needed by `root`.
+ */
+pub fn build_dependency_graph(
+    machines: &HashMap<String, Machine>,
+    root: Option<&str>,
+) -> DependencyGraph {
+    let mut graph = DependencyGraph {
+        nodes: BTreeMap::new(),
+        edges: Vec::new(),
+    };
+
+    let mut pending: VecDeque<&str> = match root {
+        Some(root) => VecDeque::from([root]),
+        None => machines.keys().map(|name| name.as_str()).collect(),
+    };
+
+    while let Some(name) = pending.pop_front() {
+        if graph.nodes.contains_key(name) {
+            continue;
+        }
+
+        let machine = match machines.get(name) {
+            Some(machine) => machine,
+            None => {
+                graph.nodes.insert(name.to_string(), "missing");
+                continue;
+            }
+        };
+        graph.nodes.insert(name.to_string(), get_node_kind(machine));
+
+        for (dependency, kind) in get_dependencies(machine) {
+            graph
+                .edges
+                .push((name.to_string(), dependency.to_string(), kind));
+            pending.push_back(dependency);
+        }
+    }
+
+    graph.edges.sort();
+    graph.edges.dedup();
+
+    graph
+}
+
+/**
+ * Count how many machines reference each device and each BIOS.
+ */
+pub fn get_reference_counts(
+    machines: &HashMap<String, Machine>,
+) -> (HashMap<String, usize>, HashMap<String, usize>) {
+    let mut devices: HashMap<String, usize> = HashMap::new();
+    let mut bios: HashMap<String, usize> = HashMap::new();
+
+    for machine in machines.values() {
+        for (name, kind) in get_dependencies(machine) {
+            match kind {
+                "device" => *devices.entry(name.to_string()).or_insert(0) += 1,
+                "BIOS" => *bios.entry(name.to_string()).or_insert(0) += 1,
+                _ => {}
+            }
+        }
+    }
+
+    (devices, bios)
+}
+
+/**
+ * Get the longest chain of dependencies of each machine, starting with the machine itself.
+ */
+pub fn get_dependency_chains(machines: &HashMap<String, Machine>) -> HashMap<String, Vec<String>> {
+    let mut chains: HashMap<String, Vec<String>> = HashMap::new();
+    for name in machines.keys() {
+        let mut visiting = Vec::new();
+        get_dependency_chain(machines, name, &mut chains, &mut visiting);
+    }
+
+    chains
+}
+
+/**
+ * Get the longest chain of dependencies of a machine, remembering the chains already found.
+ * Dependencies already in the chain being followed are skipped to avoid cycles.
+ */
+fn get_dependency_chain(
+    machines: &HashMap<String, Machine>,
+    name: &str,
+    chains: &mut HashMap<String, Vec<String>>,
+    visiting: &mut Vec<String>,
+) -> Vec<String> {
+    if let Some(chain) = chains.get(name) {
+        return chain.clone();
+    }
+
+    let mut longest: Vec<String> = Vec::new();
+    if let Some(machine) = machines.get(name) {
+        visiting.push(name.to_string());
+        for (dependency, _) in get_dependencies(machine) {
+            if visiting.iter().any(|visited| visited == dependency) {
+                continue;
+            }
+            let chain = get_dependency_chain(machines, dependency, chains, visiting);
+            if chain.len() > longest.len() {
+                longest = chain;
+            }
+        }
+        visiting.pop();
+    }
+
+    let mut chain = vec![name.to_string()];
+    chain.extend(longest);
+    chains.insert(name.to_string(), chain.clone());
+
+    chain
+}
+
+/**
+ * Write the graph in Graphviz DOT format.
+ */
+pub fn write_dot(file_path: &Path, graph: &DependencyGraph) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = BufWriter::new(File::create(file_path)?);
+
+    writeln!(file, "digraph dependencies {{")?;
+    writeln!(file, "  rankdir=LR;")?;
+    writeln!(file, "  node [style=filled];")?;
+    for (name, kind) in &graph.nodes {
+        let (shape, color) = match *kind {
+            "BIOS" => ("box", "lightgoldenrod"),
+            "device" => ("ellipse", "lightblue"),
+            "missing" => ("ellipse", "lightgray"),
+            _ => ("box", "palegreen"),
+        };
+        writeln!(
+            file,
+            "  \"{}\" [shape={}, fillcolor={}, kind=\"{}\"];",
+            escape_dot(name),
+            shape,
+            color,
+            kind
+        )?;
+    }
+    for (from, to, kind) in &graph.edges {
+        let style = match *kind {
+            "parent" => "solid",
+            "BIOS" => "bold",
+            _ => "dashed",
+        };
+        writeln!(
+            file,
+            "  \"{}\" -> \"{}\" [label=\"{}\", style={}];",
+            escape_dot(from),
+            escape_dot(to),
+            kind,
+            style
+        )?;
+    }
+    writeln!(file, "}}")?;
+
+    file.flush()?;
+
+    Ok(())
+}
+
+/**
+ * Write the graph in GraphML format.
+ */
+pub fn write_graphml(file_path: &Path, graph: &DependencyGraph) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = BufWriter::new(File::create(file_path)?);
+
+    writeln!(file, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        file,
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+    )?;
+    writeln!(
+        file,
+        "  <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>"
+    )?;
+    writeln!(
+        file,
+        "  <key id=\"dependency\" for=\"edge\" attr.name=\"dependency\" attr.type=\"string\"/>"
+    )?;
+    writeln!(
+        file,
+        "  <graph id=\"dependencies\" edgedefault=\"directed\">"
+    )?;
+    for (name, kind) in &graph.nodes {
+        writeln!(
+            file,
+            "    <node id=\"{}\"><data key=\"kind\">{}</data></node>",
+            escape_xml(name),
+            kind
+        )?;
+    }
+    for (index, (from, to, kind)) in graph.edges.iter().enumerate() {
+        writeln!(
+            file,
+            "    <edge id=\"e{}\" source=\"{}\" target=\"{}\"><data key=\"dependency\">{}</data></edge>",
+            index,
+            escape_xml(from),
+            escape_xml(to),
+            kind
+        )?;
+    }
+    writeln!(file, "  </graph>")?;
+    writeln!(file, "</graphml>")?;
+
+    file.flush()?;
+
+    Ok(())
+}
+
+/**
+ * Get the kind of node of a machine in the data set: BIOS, device or machine.
+ */
+fn get_node_kind(machine: &Machine) -> &'static str {
+    if machine.is_bios == Some(true) {
+        "BIOS"
+    } else if machine.is_device == Some(true) {
+        "device"
+    } else {
+        "machine"
+    }
+}
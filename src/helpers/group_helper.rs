@@ -0,0 +1,235 @@
+use crate::helpers::machine_helper::format_flag;
+use mame_parser::models::Machine;
+use std::collections::{BTreeSet, HashMap};
+
+/**
+ * Fields the machines can be grouped by, with the name shown to the user.
+ */
+pub const GROUP_FIELDS: &[(&str, &str)] = &[
+    ("year", "Year"),
+    ("decade", "Decade"),
+    ("manufacturer", "Manufacturer"),
+    ("source_file", "Source file"),
+    ("driver_status", "Driver status"),
+    ("category", "Category"),
+    ("subcategory", "Category - Subcategory"),
+    ("series", "Series"),
+    ("languages", "Language"),
+    ("players", "Players"),
+    ("is_mature", "Mature"),
+    ("is_parent", "Parent / clone"),
+    ("is_bios", "BIOS"),
+    ("is_device", "Device"),
+    ("is_mechanical", "Mechanical"),
+    ("runnable", "Runnable"),
+    ("resources", "Resource type"),
+];
+
+/**
+ * Value used for the machines without a value for a field.
+ */
+pub const UNKNOWN_VALUE: &str = "Unknown";
+
+/**
+ * Count the machines by the values of one or more fields.
+ * Machines with several values for a field, such as languages or resource types, are
+ * counted once for each value, and the values of several fields are joined with " / ".
+ */
+pub fn group_by(machines: &HashMap<String, Machine>, fields: &[&str]) -> HashMap<String, usize> {
+    let mut groups: HashMap<String, usize> = HashMap::new();
+
+    for machine in machines.values() {
+        for key in get_group_keys(machine, fields) {
+            *groups.entry(key).or_insert(0) += 1;
+        }
+    }
+
+    groups
+}
+
+/**
+ * Count of machines for each combination of the values of two fields.
+ */
+pub struct Pivot {
+    pub rows: Vec<String>,
+    pub columns: Vec<String>,
+    pub counts: HashMap<(String, String), usize>,
+    /**
+     * Machines with each value of the row field, counted once even if they are in several columns.
+     */
+    pub row_totals: HashMap<String, usize>,
+    /**
+     * Machines with each value of the column field, counted once even if they are in several rows.
+     */
+    pub column_totals: HashMap<String, usize>,
+    pub total: usize,
+}
+
+/**
+ * Cross-tabulate the machines by the values of two fields. The rows and columns are sorted by
+ * their total, except for years and decades which are sorted chronologically.
+ */
+pub fn pivot(machines: &HashMap<String, Machine>, row_field: &str, column_field: &str) -> Pivot {
+    let mut counts: HashMap<(String, String), usize> = HashMap::new();
+    let mut row_totals: HashMap<String, usize> = HashMap::new();
+    let mut column_totals: HashMap<String, usize> = HashMap::new();
+
+    for machine in machines.values() {
+        let row_values = get_field_values(machine, row_field);
+        let column_values = get_field_values(machine, column_field);
+
+        for row in &row_values {
+            *row_totals.entry(row.clone()).or_insert(0) += 1;
+            for column in &column_values {
+                *counts.entry((row.clone(), column.clone())).or_insert(0) += 1;
+            }
+        }
+        for column in column_values {
+            *column_totals.entry(column).or_insert(0) += 1;
+        }
+    }
+
+    Pivot {
+        rows: sort_values(&row_totals, row_field),
+        columns: sort_values(&column_totals, column_field),
+        counts,
+        row_totals,
+        column_totals,
+        total: machines.len(),
+    }
+}
+
+/**
+ * Get the name shown to the user for a field.
+ */
+pub fn get_field_label(field: &str) -> &'static str {
+    GROUP_FIELDS
+        .iter()
+        .find(|(name, _)| *name == field)
+        .map(|(_, label)| *label)
+        .unwrap()
+}
+
+/**
+ * Get the values of a field for a machine, sorted and without duplicates.
+ */
+pub fn get_field_values(machine: &Machine, field: &str) -> Vec<String> {
+    let values: Vec<String> = match field {
+        "year" => machine.year.iter().cloned().collect(),
+        "decade" => machine
+            .year
+            .iter()
+            .filter_map(|year| get_decade(year))
+            .collect(),
+        "manufacturer" => machine.manufacturer.iter().cloned().collect(),
+        "source_file" => machine.source_file.iter().cloned().collect(),
+        "driver_status" => machine.driver_status.iter().cloned().collect(),
+        "category" => machine.category.iter().cloned().collect(),
+        "subcategory" => match (&machine.category, &machine.subcategory) {
+            (Some(category), Some(subcategory)) => vec![format!("{} - {}", category, subcategory)],
+            _ => Vec::new(),
+        },
+        "series" => machine.series.iter().cloned().collect(),
+        "languages" => machine.languages.clone(),
+        "players" => machine.players.iter().cloned().collect(),
+        "is_mature" => get_flag_values(machine.is_mature),
+        "is_parent" => vec![if machine.clone_of.is_some() {
+            "Clone".to_string()
+        } else {
+            "Parent".to_string()
+        }],
+        "is_bios" => get_flag_values(machine.is_bios),
+        "is_device" => get_flag_values(machine.is_device),
+        "is_mechanical" => get_flag_values(machine.is_mechanical),
+        "runnable" => get_flag_values(machine.runnable),
+        "resources" => machine
+            .resources
+            .iter()
+            .map(|resource| resource.type_.clone())
+            .collect(),
+        _ => unreachable!(),
+    };
+
+    let values: BTreeSet<String> = values.into_iter().collect();
+    if values.is_empty() {
+        return vec![UNKNOWN_VALUE.to_string()];
+    }
+
+    values.into_iter().collect()
+}
+
+/**
+ * Get the keys of the groups a machine belongs to, one for each combination of values.
+ */
+fn get_group_keys(machine: &Machine, fields: &[&str]) -> Vec<String> {
+    let mut keys = vec![String::new()];
+
+    for (index, field) in fields.iter().enumerate() {
+        let values = get_field_values(machine, field);
+        keys = keys
+            .iter()
+            .flat_map(|key| {
+                values.iter().map(move |value| {
+                    if index == 0 {
+                        value.clone()
+                    } else {
+                        format!("{} / {}", key, value)
+                    }
+                })
+            })
+            .collect();
+    }
+
+    keys
+}
+
+/**
+ * Sort the values of a field by total and name, or chronologically for years and decades.
+ */
+fn sort_values(totals: &HashMap<String, usize>, field: &str) -> Vec<String> {
+    let mut values: Vec<(&String, &usize)> = totals.iter().collect();
+    if field == "year" || field == "decade" {
+        values.sort_by(|a, b| a.0.cmp(b.0));
+    } else {
+        values.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    }
+
+    values.into_iter().map(|(value, _)| value.clone()).collect()
+}
+
+/**
+ * Get the decade of a year, also for partially known years such as `198?`.
+ */
+fn get_decade(year: &str) -> Option<String> {
+    let digits = year.get(0..3)?;
+    if digits.chars().all(|c| c.is_ascii_digit()) {
+        Some(format!("{}0s", digits))
+    } else {
+        None
+    }
+}
+
+/**
+ * Get the values of a flag field, empty when the flag is unknown.
+ */
+fn get_flag_values(value: Option<bool>) -> Vec<String> {
+    format_flag(value).into_iter().map(String::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gets_the_decade_of_a_year() {
+        assert_eq!(get_decade("1985"), Some("1980s".to_string()));
+        assert_eq!(get_decade("198?"), Some("1980s".to_string()));
+    }
+
+    #[test]
+    fn ignores_years_without_a_known_decade() {
+        assert_eq!(get_decade("19??"), None);
+        assert_eq!(get_decade("?"), None);
+        assert_eq!(get_decade(""), None);
+    }
+}
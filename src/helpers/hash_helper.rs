@@ -0,0 +1,302 @@
+use crate::helpers::ui_helper::icons::ERROR;
+use indicatif::ProgressBar;
+use sha1::{Digest, Sha1};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+use zip::ZipArchive;
+
+/**
+ * Size and checksums of a file.
+ */
+#[derive(Debug, Clone)]
+pub struct FileHashes {
+    pub size: u64,
+    pub crc: String,
+    pub sha1: String,
+}
+
+/**
+ * Location of a hashed file, either a plain file or an entry inside a zip archive.
+ */
+#[derive(Debug, Clone)]
+pub enum FileLocation {
+    File(PathBuf),
+    ZipEntry(PathBuf, String),
+}
+
+/**
+ * A file found on disk together with its checksums.
+ */
+#[derive(Debug, Clone)]
+pub struct HashedFile {
+    pub location: FileLocation,
+    pub hashes: FileHashes,
+}
+
+impl HashedFile {
+    /**
+     * Read the content of the file from its location.
+     */
+    pub fn read(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        match &self.location {
+            FileLocation::File(path) => Ok(fs::read(path)?),
+            FileLocation::ZipEntry(path, entry_name) => {
+                let mut archive = ZipArchive::new(File::open(path)?)?;
+                let mut entry = archive.by_name(entry_name)?;
+                let mut data = Vec::with_capacity(entry.size() as usize);
+                entry.read_to_end(&mut data)?;
+                Ok(data)
+            }
+        }
+    }
+
+    /**
+     * Get a printable description of the file location.
+     */
+    pub fn display_name(&self) -> String {
+        match &self.location {
+            FileLocation::File(path) => path.display().to_string(),
+            FileLocation::ZipEntry(path, entry_name) => {
+                format!("{} ({})", path.display(), entry_name)
+            }
+        }
+    }
+}
+
+/**
+ * A file that could not be read or hashed, with the reason.
+ */
+#[derive(Debug, Clone)]
+pub struct UnreadableFile {
+    pub name: String,
+    pub error: String,
+}
+
+/**
+ * Calculate the size, CRC32 and SHA1 of a file, reading it in blocks so large files are not
+ * loaded in memory.
+ */
+pub fn hash_file(path: &Path) -> Result<FileHashes, Box<dyn Error>> {
+    Ok(hash_reader(&mut File::open(path)?)?)
+}
+
+/**
+ * Calculate the size, CRC32 and SHA1 of the data read from a reader, block by block.
+ */
+fn hash_reader(reader: &mut impl Read) -> io::Result<FileHashes> {
+    let mut crc_hasher = crc32fast::Hasher::new();
+    let mut sha1_hasher = Sha1::new();
+    let mut size = 0u64;
+    let mut buffer = vec![0u8; 64 * 1024];
+
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        crc_hasher.update(&buffer[..read]);
+        sha1_hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+
+    Ok(FileHashes {
+        size,
+        crc: format!("{:08x}", crc_hasher.finalize()),
+        sha1: sha1_hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect(),
+    })
+}
+
+/**
+ * Hash every file found in the given paths.
+ * Paths can be folders (walked recursively), zip archives or plain files.
+ * The content of zip archives is hashed entry by entry and CHD files use the SHA1 from their header.
+ * Files that cannot be read are returned apart, with the reason, without stopping the process.
+ */
+pub fn hash_files_in_paths(
+    paths: &[PathBuf],
+    progress_bar: &ProgressBar,
+) -> Result<(Vec<HashedFile>, Vec<UnreadableFile>), Box<dyn Error>> {
+    let mut hashed_files = Vec::new();
+    let mut unreadable_files = Vec::new();
+
+    for path in paths {
+        if !path.exists() {
+            return Err(format!("Path {} not found", path.display()).into());
+        }
+
+        for entry in WalkDir::new(path) {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    add_unreadable_file(
+                        &mut unreadable_files,
+                        progress_bar,
+                        err.path().unwrap_or(path).display().to_string(),
+                        err.to_string(),
+                    );
+                    continue;
+                }
+            };
+            if !entry.path().is_file() {
+                continue;
+            }
+            progress_bar.set_message(entry.path().display().to_string());
+
+            if has_extension(entry.path(), "zip") {
+                if let Err(err) = hash_zip_entries(
+                    entry.path(),
+                    &mut hashed_files,
+                    &mut unreadable_files,
+                    progress_bar,
+                ) {
+                    add_unreadable_file(
+                        &mut unreadable_files,
+                        progress_bar,
+                        entry.path().display().to_string(),
+                        err.to_string(),
+                    );
+                }
+            } else {
+                let hashes = if has_extension(entry.path(), "chd") {
+                    read_chd_hashes(entry.path())
+                } else {
+                    hash_file(entry.path())
+                };
+                match hashes {
+                    Ok(hashes) => hashed_files.push(HashedFile {
+                        location: FileLocation::File(entry.path().to_path_buf()),
+                        hashes,
+                    }),
+                    Err(err) => add_unreadable_file(
+                        &mut unreadable_files,
+                        progress_bar,
+                        entry.path().display().to_string(),
+                        err.to_string(),
+                    ),
+                }
+            }
+            progress_bar.inc(1);
+        }
+    }
+
+    Ok((hashed_files, unreadable_files))
+}
+
+/**
+ * Add a file to the unreadable files, logging it above the progress bar.
+ */
+fn add_unreadable_file(
+    unreadable_files: &mut Vec<UnreadableFile>,
+    progress_bar: &ProgressBar,
+    name: String,
+    error: String,
+) {
+    progress_bar.println(format!("{} Skipping {}: {}", ERROR, name, error));
+    unreadable_files.push(UnreadableFile { name, error });
+}
+
+/**
+ * Check if the path has the given extension.
+ */
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .map(|file_extension| file_extension.eq_ignore_ascii_case(extension))
+        .unwrap_or(false)
+}
+
+/**
+ * Read the SHA1 stored in the header of a CHD file.
+ * This is the SHA1 listed in the MAME disks, hashing the whole file would give a different value.
+ */
+fn read_chd_hashes(path: &Path) -> Result<FileHashes, Box<dyn Error>> {
+    let mut header = [0u8; 124];
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let read = file.read(&mut header)?;
+
+    if read < 16 || &header[0..8] != b"MComprHD" {
+        return Err(format!("{} is not a valid CHD file", path.display()).into());
+    }
+
+    let version = u32::from_be_bytes([header[12], header[13], header[14], header[15]]);
+    let sha1_offset = match version {
+        3 => 80,
+        4 => 48,
+        5 => 84,
+        _ => {
+            return Err(format!(
+                "{} uses unsupported CHD version {}",
+                path.display(),
+                version
+            )
+            .into())
+        }
+    };
+
+    if read < sha1_offset + 20 {
+        return Err(format!("{} has a truncated CHD header", path.display()).into());
+    }
+
+    Ok(FileHashes {
+        size,
+        crc: String::new(),
+        sha1: header[sha1_offset..sha1_offset + 20]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect(),
+    })
+}
+
+/**
+ * Hash every entry of a zip archive, adding the entries that cannot be read to the unreadable
+ * files and logging them like the other unreadable files. An error is returned only when the
+ * archive itself cannot be opened.
+ */
+fn hash_zip_entries(
+    path: &Path,
+    hashed_files: &mut Vec<HashedFile>,
+    unreadable_files: &mut Vec<UnreadableFile>,
+    progress_bar: &ProgressBar,
+) -> Result<(), Box<dyn Error>> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+
+    for index in 0..archive.len() {
+        let mut entry = match archive.by_index(index) {
+            Ok(entry) => entry,
+            Err(err) => {
+                add_unreadable_file(
+                    unreadable_files,
+                    progress_bar,
+                    format!("{} (entry {})", path.display(), index + 1),
+                    err.to_string(),
+                );
+                continue;
+            }
+        };
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        match hash_reader(&mut entry) {
+            Ok(hashes) => hashed_files.push(HashedFile {
+                location: FileLocation::ZipEntry(path.to_path_buf(), name),
+                hashes,
+            }),
+            Err(err) => add_unreadable_file(
+                unreadable_files,
+                progress_bar,
+                format!("{} ({})", path.display(), name),
+                err.to_string(),
+            ),
+        }
+    }
+
+    Ok(())
+}
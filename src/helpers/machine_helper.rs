@@ -0,0 +1,29 @@
+use mame_parser::models::Machine;
+
+/**
+ * Check if the machine needs CHD disk images.
+ */
+pub fn requires_disks(machine: &Machine) -> bool {
+    !machine.disks.is_empty()
+}
+
+/**
+ * Check if the machine needs sound samples.
+ */
+pub fn requires_samples(machine: &Machine) -> bool {
+    !machine.samples.is_empty() || machine.sample_of.is_some()
+}
+
+/**
+ * Get the total size in bytes of the ROMs of the machine.
+ */
+pub fn get_total_rom_size(machine: &Machine) -> u64 {
+    machine.roms.iter().map(|rom| rom.size).sum()
+}
+
+/**
+ * Get the text shown for a flag of a machine, such as `is_bios`: "Yes", "No" or none when unknown.
+ */
+pub fn format_flag(value: Option<bool>) -> Option<&'static str> {
+    value.map(|value| if value { "Yes" } else { "No" })
+}
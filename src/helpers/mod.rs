@@ -1,4 +1,18 @@
 /**
  * This file is used to export all the helper functions
  */
+pub mod dependency_helper;
+pub mod details_helper;
+pub mod details_reader;
+pub mod details_writer;
+pub mod diff_helper;
+pub mod escape_helper;
+pub mod file_helper;
+pub mod graph_helper;
+pub mod group_helper;
+pub mod hash_helper;
+pub mod machine_helper;
+pub mod players_helper;
+pub mod report_helper;
+pub mod search_helper;
 pub mod ui_helper;
@@ -0,0 +1,142 @@
+use crate::models::player_count::PlayerCount;
+use lazy_static::lazy_static;
+use mame_parser::models::Machine;
+use regex::Regex;
+use std::collections::HashMap;
+
+/**
+ * Values of nplayers.ini that are recognized but do not describe a number of players.
+ */
+const NON_PLAYER_VALUES: &[&str] = &["pinball", "bios", "device", "non-arcade", "???"];
+
+lazy_static! {
+    static ref PLAYERS_PATTERN: Regex = Regex::new(r"(?i)^(\d+)\s*p\s*(alt|sim)?\s*(.*)$").unwrap();
+}
+
+/**
+ * Parse the players information of nplayers.ini (like "1P", "4P sim" or "4P alt / 2P sim").
+ * Returns None if the value is not a number of players.
+ */
+pub fn parse_player_count(players: &str) -> Option<PlayerCount> {
+    let mut player_count = PlayerCount::default();
+
+    for part in players.split('/') {
+        let captures = PLAYERS_PATTERN.captures(part.trim())?;
+        let total: u32 = captures[1].parse().ok()?;
+        let mode = captures.get(2).map(|m| m.as_str().to_lowercase());
+        let notes = captures[3].to_lowercase();
+
+        player_count.max_players = player_count.max_players.max(total);
+        match mode.as_deref() {
+            Some("sim") => {
+                player_count.simultaneous |= total > 1;
+                player_count.max_simultaneous = player_count.max_simultaneous.max(total);
+            }
+            Some(_) => {
+                player_count.alternating = true;
+                player_count.max_simultaneous = player_count.max_simultaneous.max(1);
+            }
+            None if total == 1 => {
+                player_count.max_simultaneous = player_count.max_simultaneous.max(1);
+            }
+            None => return None,
+        }
+
+        if notes.contains("coop") || notes.contains("co-op") {
+            player_count.coop = true;
+        }
+        if notes.contains("vs") || notes.contains("versus") {
+            player_count.versus = true;
+        }
+    }
+
+    Some(player_count)
+}
+
+/**
+ * Get the parsed player count of a machine.
+ */
+pub fn get_player_count(machine: &Machine) -> Option<PlayerCount> {
+    machine.players.as_deref().and_then(parse_player_count)
+}
+
+/**
+ * Check if a players value of nplayers.ini is understood, even if it is not a number of players.
+ */
+pub fn is_players_value_recognized(players: &str) -> bool {
+    NON_PLAYER_VALUES.contains(&players.trim().to_lowercase().as_str())
+        || parse_player_count(players).is_some()
+}
+
+/**
+ * Get the players values that could not be parsed with the number of machines using each one.
+ */
+pub fn get_unrecognized_players_list(
+    machines: &HashMap<String, Machine>,
+) -> HashMap<String, usize> {
+    let mut unrecognized: HashMap<String, usize> = HashMap::new();
+
+    for machine in machines.values() {
+        if let Some(players) = &machine.players {
+            if !is_players_value_recognized(players) {
+                *unrecognized.entry(players.clone()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    unrecognized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_single_player() {
+        let player_count = parse_player_count("1P").unwrap();
+        assert_eq!(player_count.max_players, 1);
+        assert_eq!(player_count.max_simultaneous, 1);
+        assert!(!player_count.simultaneous);
+        assert!(!player_count.alternating);
+    }
+
+    #[test]
+    fn parses_simultaneous_players_with_notes() {
+        let player_count = parse_player_count("2P sim coop").unwrap();
+        assert_eq!(player_count.max_players, 2);
+        assert_eq!(player_count.max_simultaneous, 2);
+        assert!(player_count.simultaneous);
+        assert!(player_count.coop);
+        assert!(!player_count.versus);
+    }
+
+    #[test]
+    fn parses_alternating_and_simultaneous_parts() {
+        let player_count = parse_player_count("4P alt / 2P sim").unwrap();
+        assert_eq!(player_count.max_players, 4);
+        assert_eq!(player_count.max_simultaneous, 2);
+        assert!(player_count.alternating);
+        assert!(player_count.simultaneous);
+    }
+
+    #[test]
+    fn keeps_simultaneous_when_a_later_part_is_single_player() {
+        let player_count = parse_player_count("2P sim / 1P sim").unwrap();
+        assert_eq!(player_count.max_simultaneous, 2);
+        assert!(player_count.simultaneous);
+    }
+
+    #[test]
+    fn rejects_values_that_are_not_players() {
+        assert_eq!(parse_player_count("Pinball"), None);
+        assert_eq!(parse_player_count("2P"), None);
+        assert_eq!(parse_player_count(""), None);
+    }
+
+    #[test]
+    fn recognizes_values_that_are_not_players() {
+        assert!(is_players_value_recognized("Pinball"));
+        assert!(is_players_value_recognized("3P sim"));
+        assert!(!is_players_value_recognized("2P"));
+    }
+}
@@ -0,0 +1,464 @@
+use crate::helpers::escape_helper::{escape_markdown, escape_xml};
+use crate::models::stats_table::StatsTable;
+use num_format::{Locale, ToFormattedString};
+use prettytable::{Cell, Row, Table};
+use serde::Serialize;
+use serde_json::Value;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/**
+ * Formats the statistics can be exported to, with the extension of their files.
+ */
+pub const REPORT_FORMATS: &[(&str, &str)] = &[
+    ("Markdown", "md"),
+    ("CSV", "csv"),
+    ("JSON", "json"),
+    ("HTML", "html"),
+];
+
+/**
+ * Report written to JSON, keeping the order of its fields.
+ */
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    title: &'a str,
+    generated_at: &'a str,
+    tables: Vec<JsonTable<'a>>,
+}
+
+/**
+ * Table written to JSON, with the values of the numeric columns as numbers.
+ */
+#[derive(Serialize)]
+struct JsonTable<'a> {
+    title: &'a str,
+    headers: &'a [String],
+    rows: Vec<Vec<Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    totals: Option<Vec<Value>>,
+}
+
+/**
+ * Print a statistics table to the terminal, with the numbers right aligned and the totals in bold.
+ */
+pub fn print_stats_table(stats_table: &StatsTable) {
+    let mut table = Table::new();
+    table.set_titles(Row::new(vec![
+        Cell::new(&stats_table.title).style_spec(&format!("H{}cFg", stats_table.headers.len()))
+    ]));
+
+    table.add_row(Row::new(
+        stats_table
+            .headers
+            .iter()
+            .map(|header| Cell::new(header).style_spec("b"))
+            .collect(),
+    ));
+
+    for row in &stats_table.rows {
+        table.add_row(get_row(stats_table, row, false));
+    }
+    if let Some(totals) = &stats_table.totals {
+        table.add_row(get_row(stats_table, totals, true));
+    }
+
+    table.printstd();
+}
+
+/**
+ * Write the tables to a report file, in the format given by the extension of the file.
+ */
+pub fn write_report(
+    file_path: &Path,
+    title: &str,
+    tables: &[StatsTable],
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let generated_at = chrono::Local::now().format("%Y-%m-%d %H:%M").to_string();
+
+    match file_path
+        .extension()
+        .and_then(|extension| extension.to_str())
+    {
+        Some("md") => write_markdown(file_path, title, &generated_at, tables),
+        Some("csv") => write_csv(file_path, tables),
+        Some("json") => write_json(file_path, title, &generated_at, tables),
+        Some("html") => write_html(file_path, title, &generated_at, tables),
+        _ => Err(format!("Unsupported report format: {}", file_path.display()).into()),
+    }
+}
+
+/**
+ * Write the tables as Markdown, with a heading for each table.
+ */
+fn write_markdown(
+    file_path: &Path,
+    title: &str,
+    generated_at: &str,
+    tables: &[StatsTable],
+) -> Result<(), Box<dyn Error>> {
+    let mut file = BufWriter::new(File::create(file_path)?);
+
+    writeln!(file, "# {}", escape_markdown(title))?;
+    writeln!(file)?;
+    writeln!(file, "Generated on {}.", generated_at)?;
+
+    for table in tables {
+        writeln!(file)?;
+        writeln!(file, "## {}", escape_markdown(&table.title))?;
+        writeln!(file)?;
+
+        let headers: Vec<String> = table
+            .headers
+            .iter()
+            .map(|header| escape_markdown(header))
+            .collect();
+        writeln!(file, "| {} |", headers.join(" | "))?;
+
+        let alignments: Vec<&str> = (0..table.headers.len())
+            .map(|column| {
+                if table.numeric_columns.contains(&column) {
+                    "---:"
+                } else {
+                    "---"
+                }
+            })
+            .collect();
+        writeln!(file, "| {} |", alignments.join(" | "))?;
+
+        for row in &table.rows {
+            let cells: Vec<String> = get_values(table, row)
+                .iter()
+                .map(|value| escape_markdown(value))
+                .collect();
+            writeln!(file, "| {} |", cells.join(" | "))?;
+        }
+        if let Some(totals) = &table.totals {
+            let cells: Vec<String> = get_values(table, totals)
+                .iter()
+                .map(|value| {
+                    if value.is_empty() {
+                        String::new()
+                    } else {
+                        format!("**{}**", escape_markdown(value))
+                    }
+                })
+                .collect();
+            writeln!(file, "| {} |", cells.join(" | "))?;
+        }
+    }
+
+    file.flush()?;
+
+    Ok(())
+}
+
+/**
+ * Write the tables as CSV. A single table is written as is, while several tables are each
+ * preceded by a line with their title and separated by an empty line.
+ */
+fn write_csv(file_path: &Path, tables: &[StatsTable]) -> Result<(), Box<dyn Error>> {
+    let mut file = BufWriter::new(File::create(file_path)?);
+
+    for (index, table) in tables.iter().enumerate() {
+        if index > 0 {
+            writeln!(file)?;
+        }
+
+        let mut writer = csv::WriterBuilder::new()
+            .flexible(true)
+            .from_writer(&mut file);
+        if tables.len() > 1 {
+            writer.write_record([&table.title])?;
+        }
+        writer.write_record(&table.headers)?;
+        for row in &table.rows {
+            writer.write_record(row)?;
+        }
+        if let Some(totals) = &table.totals {
+            writer.write_record(totals)?;
+        }
+        writer.flush()?;
+    }
+
+    file.flush()?;
+
+    Ok(())
+}
+
+/**
+ * Write the tables as JSON, with the values of the numeric columns written as numbers.
+ */
+fn write_json(
+    file_path: &Path,
+    title: &str,
+    generated_at: &str,
+    tables: &[StatsTable],
+) -> Result<(), Box<dyn Error>> {
+    let report = JsonReport {
+        title,
+        generated_at,
+        tables: tables
+            .iter()
+            .map(|table| JsonTable {
+                title: &table.title,
+                headers: &table.headers,
+                rows: table
+                    .rows
+                    .iter()
+                    .map(|row| get_json_values(table, row))
+                    .collect(),
+                totals: table
+                    .totals
+                    .as_ref()
+                    .map(|totals| get_json_values(table, totals)),
+            })
+            .collect(),
+    };
+
+    let file = BufWriter::new(File::create(file_path)?);
+    serde_json::to_writer_pretty(file, &report)?;
+
+    Ok(())
+}
+
+/**
+ * Write the tables as a standalone HTML document, with its styles included.
+ */
+fn write_html(
+    file_path: &Path,
+    title: &str,
+    generated_at: &str,
+    tables: &[StatsTable],
+) -> Result<(), Box<dyn Error>> {
+    let mut file = BufWriter::new(File::create(file_path)?);
+
+    writeln!(file, "<!DOCTYPE html>")?;
+    writeln!(file, "<html lang=\"en\">")?;
+    writeln!(file, "<head>")?;
+    writeln!(file, "<meta charset=\"UTF-8\">")?;
+    writeln!(file, "<title>{}</title>", escape_xml(title))?;
+    writeln!(file, "<style>")?;
+    writeln!(
+        file,
+        "body {{ font-family: sans-serif; margin: 2em; color: #222; }}"
+    )?;
+    writeln!(
+        file,
+        "table {{ border-collapse: collapse; margin-bottom: 2em; }}"
+    )?;
+    writeln!(
+        file,
+        "caption {{ font-weight: bold; text-align: left; padding: 0.5em 0; color: #2e7d32; }}"
+    )?;
+    writeln!(
+        file,
+        "th, td {{ border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }}"
+    )?;
+    writeln!(file, "th {{ background: #f0f0f0; }}")?;
+    writeln!(file, ".number {{ text-align: right; }}")?;
+    writeln!(file, "tfoot td {{ font-weight: bold; }}")?;
+    writeln!(file, "</style>")?;
+    writeln!(file, "</head>")?;
+    writeln!(file, "<body>")?;
+    writeln!(file, "<h1>{}</h1>", escape_xml(title))?;
+    writeln!(file, "<p>Generated on {}.</p>", generated_at)?;
+
+    for table in tables {
+        writeln!(file, "<table>")?;
+        writeln!(file, "<caption>{}</caption>", escape_xml(&table.title))?;
+        writeln!(file, "<thead>")?;
+        writeln!(
+            file,
+            "<tr>{}</tr>",
+            get_html_cells(table, &table.headers, "th")
+        )?;
+        writeln!(file, "</thead>")?;
+        writeln!(file, "<tbody>")?;
+        for row in &table.rows {
+            let values = get_values(table, row);
+            writeln!(file, "<tr>{}</tr>", get_html_cells(table, &values, "td"))?;
+        }
+        writeln!(file, "</tbody>")?;
+        if let Some(totals) = &table.totals {
+            let values = get_values(table, totals);
+            writeln!(file, "<tfoot>")?;
+            writeln!(file, "<tr>{}</tr>", get_html_cells(table, &values, "td"))?;
+            writeln!(file, "</tfoot>")?;
+        }
+        writeln!(file, "</table>")?;
+    }
+
+    writeln!(file, "</body>")?;
+    writeln!(file, "</html>")?;
+
+    file.flush()?;
+
+    Ok(())
+}
+
+/**
+ * Get the cells of a row for the terminal.
+ */
+fn get_row(table: &StatsTable, row: &[String], bold: bool) -> Row {
+    Row::new(
+        get_values(table, row)
+            .iter()
+            .enumerate()
+            .map(|(column, value)| {
+                let style = match (table.numeric_columns.contains(&column), bold) {
+                    (true, true) => "rb",
+                    (true, false) => "r",
+                    (false, true) => "b",
+                    (false, false) => "",
+                };
+                Cell::new(value).style_spec(style)
+            })
+            .collect(),
+    )
+}
+
+/**
+ * Get the values of a row as shown to the user, with thousands separators in the numbers.
+ */
+fn get_values(table: &StatsTable, row: &[String]) -> Vec<String> {
+    row.iter()
+        .enumerate()
+        .map(|(column, value)| {
+            match (
+                table.numeric_columns.contains(&column),
+                value.parse::<u64>(),
+            ) {
+                (true, Ok(number)) => number.to_formatted_string(&Locale::en),
+                _ => value.clone(),
+            }
+        })
+        .collect()
+}
+
+/**
+ * Get the values of a row for JSON, with the values of the numeric columns as numbers.
+ */
+fn get_json_values(table: &StatsTable, row: &[String]) -> Vec<Value> {
+    row.iter()
+        .enumerate()
+        .map(|(column, value)| {
+            if !table.numeric_columns.contains(&column) {
+                return Value::from(value.as_str());
+            }
+            if let Ok(number) = value.parse::<u64>() {
+                Value::from(number)
+            } else if let Ok(number) = value.parse::<f64>() {
+                Value::from(number)
+            } else {
+                Value::from(value.as_str())
+            }
+        })
+        .collect()
+}
+
+/**
+ * Get the HTML cells of a row, using the given tag and aligning the numeric columns to the right.
+ */
+fn get_html_cells(table: &StatsTable, values: &[String], tag: &str) -> String {
+    values
+        .iter()
+        .enumerate()
+        .map(|(column, value)| {
+            if table.numeric_columns.contains(&column) {
+                format!("<{0} class=\"number\">{1}</{0}>", tag, escape_xml(value))
+            } else {
+                format!("<{0}>{1}</{0}>", tag, escape_xml(value))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_table() -> StatsTable {
+        StatsTable {
+            title: "Top manufacturers".to_string(),
+            headers: vec![
+                "#".to_string(),
+                "Manufacturer".to_string(),
+                "Machines".to_string(),
+            ],
+            rows: vec![
+                vec![
+                    "1".to_string(),
+                    "Namco | Midway".to_string(),
+                    "1234".to_string(),
+                ],
+                vec!["2".to_string(), "<Sega>".to_string(), "56".to_string()],
+            ],
+            totals: Some(vec![String::new(), "Total".to_string(), "1290".to_string()]),
+            numeric_columns: vec![0, 2],
+        }
+    }
+
+    /**
+     * Write the table in the format of the extension and read the report back.
+     */
+    fn write_and_read(extension: &str) -> String {
+        let file_path = std::env::temp_dir()
+            .join(format!("mame-data-manager-report-{}", std::process::id()))
+            .join(format!("report.{}", extension));
+        write_report(&file_path, "Stats", &[get_table()]).unwrap();
+        let content = fs::read_to_string(&file_path).unwrap();
+        fs::remove_file(&file_path).unwrap();
+        content
+    }
+
+    #[test]
+    fn writes_markdown_with_aligned_numbers_and_bold_totals() {
+        let content = write_and_read("md");
+        assert!(content.contains("| # | Manufacturer | Machines |"));
+        assert!(content.contains("| ---: | --- | ---: |"));
+        assert!(content.contains("| 1 | Namco \\| Midway | 1,234 |"));
+        assert!(content.contains("|  | **Total** | **1,290** |"));
+    }
+
+    #[test]
+    fn writes_csv_without_thousands_separators() {
+        let content = write_and_read("csv");
+        assert_eq!(
+            content,
+            "#,Manufacturer,Machines\n1,Namco | Midway,1234\n2,<Sega>,56\n,Total,1290\n"
+        );
+    }
+
+    #[test]
+    fn writes_json_with_numbers() {
+        let content = write_and_read("json");
+        let report: Value = serde_json::from_str(&content).unwrap();
+        let table = &report["tables"][0];
+        assert_eq!(
+            table["rows"][0],
+            serde_json::json!([1, "Namco | Midway", 1234])
+        );
+        assert_eq!(table["totals"], serde_json::json!(["", "Total", 1290]));
+    }
+
+    #[test]
+    fn writes_html_with_escaped_values() {
+        let content = write_and_read("html");
+        assert!(content.contains("<td>&lt;Sega&gt;</td>"));
+        assert!(content.contains("<td class=\"number\">1,234</td>"));
+        assert!(content.contains("<tfoot>"));
+    }
+
+    #[test]
+    fn rejects_unknown_formats() {
+        let file_path = std::env::temp_dir().join("report.txt");
+        assert!(write_report(&file_path, "Stats", &[get_table()]).is_err());
+    }
+}
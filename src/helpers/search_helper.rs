@@ -0,0 +1,238 @@
+use mame_parser::models::Machine;
+use std::collections::HashMap;
+
+/**
+ * Minimum score, from 0 to 100, for a machine to be a fuzzy search result.
+ */
+pub const MIN_FUZZY_SCORE: f64 = 60.0;
+
+/**
+ * Search text split into words, ignoring case and punctuation.
+ */
+pub struct FuzzyQuery {
+    words: Vec<Vec<char>>,
+    compact: String,
+    /**
+     * Similarity of each word already seen with every word of the query, as most words
+     * repeat across the descriptions.
+     */
+    similarities: HashMap<String, Vec<f64>>,
+}
+
+impl FuzzyQuery {
+    /**
+     * Prepare a query from the text entered by the user.
+     */
+    pub fn new(text: &str) -> Self {
+        let words = split_words(text);
+        let compact = words.concat();
+        FuzzyQuery {
+            words: words.iter().map(|word| word.chars().collect()).collect(),
+            compact,
+            similarities: HashMap::new(),
+        }
+    }
+
+    /**
+     * Check if the query has no words to search, for example when only punctuation was entered.
+     */
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /**
+     * Score how well a text matches the query, from 0 to 100.
+     * Texts containing the query without spaces and punctuation score 90 or more,
+     * otherwise each word of the query is matched with the closest word of the text
+     * allowing typos.
+     */
+    pub fn score(&mut self, text: &str) -> f64 {
+        let text = text.to_lowercase();
+        let words: Vec<&str> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect();
+        if words.is_empty() || self.words.is_empty() {
+            return 0.0;
+        }
+
+        let compact = words.concat();
+        if compact.contains(&self.compact) {
+            return 90.0 + 10.0 * self.compact.len() as f64 / compact.len() as f64;
+        }
+
+        let mut best = vec![0.0; self.words.len()];
+        for word in &words {
+            if !self.similarities.contains_key(*word) {
+                let similarities = self
+                    .words
+                    .iter()
+                    .map(|query_word| get_word_similarity(query_word, word))
+                    .collect();
+                self.similarities.insert(word.to_string(), similarities);
+            }
+            for (best, similarity) in best.iter_mut().zip(&self.similarities[*word]) {
+                *best = f64::max(*best, *similarity);
+            }
+        }
+
+        let similarity = best.iter().sum::<f64>() / self.words.len() as f64;
+        let coverage = (self.words.len() as f64 / words.len() as f64).min(1.0);
+
+        (similarity * 85.0 + coverage * 5.0).min(89.9)
+    }
+}
+
+/**
+ * Find the machines matching a fuzzy query by description, name or normalized name,
+ * sorted by score and then by name.
+ */
+pub fn fuzzy_search(
+    machines: &HashMap<String, Machine>,
+    query: &mut FuzzyQuery,
+    min_score: f64,
+) -> Vec<(String, f64)> {
+    let mut results: Vec<(String, f64)> = machines
+        .values()
+        .filter_map(|machine| {
+            let normalized_name = machine
+                .extended_data
+                .as_ref()
+                .and_then(|extended_data| extended_data.name.as_deref());
+            let score = [
+                Some(machine.name.as_str()),
+                machine.description.as_deref(),
+                normalized_name,
+            ]
+            .into_iter()
+            .flatten()
+            .map(|text| query.score(text))
+            .fold(0.0, f64::max);
+
+            if score >= min_score {
+                Some((machine.name.clone(), (score * 10.0).round() / 10.0))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then(a.0.cmp(&b.0)));
+
+    results
+}
+
+/**
+ * Split a text into lowercase words of letters and digits.
+ */
+fn split_words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_string())
+        .collect()
+}
+
+/**
+ * Get the similarity between a query word and a word of the text, from 0 to 1.
+ * A query word that starts the text word, as when typing, scores almost as an exact match.
+ */
+fn get_word_similarity(query_word: &[char], word: &str) -> f64 {
+    let word_length = word.chars().count();
+    let max_length = query_word.len().max(word_length);
+    let length_difference = query_word.len().abs_diff(word_length);
+
+    if query_word
+        .iter()
+        .copied()
+        .eq(word.chars().take(query_word.len()))
+    {
+        if length_difference == 0 {
+            return 1.0;
+        }
+        if query_word.len() >= 2 {
+            return 0.95;
+        }
+    }
+
+    // Words too different in length can't be a typo of each other
+    if length_difference * 2 > max_length {
+        return 0.0;
+    }
+
+    let word: Vec<char> = word.chars().collect();
+    let distance = get_edit_distance(query_word, &word);
+
+    1.0 - distance as f64 / max_length as f64
+}
+
+/**
+ * Get the Levenshtein distance between two words, counting a swap of two adjacent
+ * letters as a single edit.
+ */
+fn get_edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous_previous: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current: Vec<usize> = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(previous_previous[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut previous_previous, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_distance(a: &str, b: &str) -> usize {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        get_edit_distance(&a, &b)
+    }
+
+    #[test]
+    fn counts_edits_between_words() {
+        assert_eq!(get_distance("galaga", "galaga"), 0);
+        assert_eq!(get_distance("kitten", "sitting"), 3);
+        assert_eq!(get_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn counts_a_swap_of_adjacent_letters_as_one_edit() {
+        assert_eq!(get_distance("fihgter", "fighter"), 1);
+    }
+
+    #[test]
+    fn scores_texts_containing_the_query_highest() {
+        let mut query = FuzzyQuery::new("Pac-Man");
+        assert_eq!(query.score("pacman"), 100.0);
+        assert!(query.score("Ms. Pac-Man") >= 90.0);
+    }
+
+    #[test]
+    fn scores_texts_with_typos_above_the_minimum() {
+        let mut query = FuzzyQuery::new("street fihgter");
+        assert!(query.score("Street Fighter II: The World Warrior") >= MIN_FUZZY_SCORE);
+        assert!(query.score("Galaga") < MIN_FUZZY_SCORE);
+    }
+
+    #[test]
+    fn scores_nothing_for_an_empty_query() {
+        let mut query = FuzzyQuery::new(" - ");
+        assert!(query.is_empty());
+        assert_eq!(query.score("Galaga"), 0.0);
+    }
+}
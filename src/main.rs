@@ -1,10 +1,15 @@
 mod helpers;
+mod models;
 mod modules;
 use dialoguer::{theme::ColorfulTheme, Select};
 use helpers::ui_helper::{show_splash_screen, show_title};
 use lazy_static::lazy_static;
 use mame_parser::models::Machine;
-use modules::{data_export, data_filtering, data_import, data_stats};
+use models::machine_details::MachineDetails;
+use modules::data_filter_log::FilterLogEntry;
+use modules::{
+    data_collections, data_export, data_filtering, data_import, data_search, data_stats,
+};
 use std::collections::HashMap;
 use std::error::Error;
 use std::sync::{Arc, Mutex};
@@ -12,6 +17,10 @@ use std::sync::{Arc, Mutex};
 lazy_static! {
     pub static ref MACHINES: Arc<Mutex<HashMap<String, Machine>>> =
         Arc::new(Mutex::new(HashMap::new()));
+    pub static ref FILTER_LOG: Arc<Mutex<Vec<FilterLogEntry>>> = Arc::new(Mutex::new(Vec::new()));
+    pub static ref MACHINE_DETAILS: Arc<Mutex<HashMap<String, MachineDetails>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    pub static ref FLAG_REQUIRED_MACHINES: Arc<Mutex<bool>> = Arc::new(Mutex::new(false));
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -30,6 +39,8 @@ fn show_main_menu() -> Result<(), Box<dyn Error>> {
             "Filter data >",
             "View statistics >",
             "Export data >",
+            "Manage collections >",
+            "Search machines >",
             "Exit",
         ];
         let selection = Select::with_theme(&ColorfulTheme::default())
@@ -43,7 +54,9 @@ fn show_main_menu() -> Result<(), Box<dyn Error>> {
             1 => data_filtering::show_filtering_submenu()?,
             2 => data_stats::show_stats_submenu()?,
             3 => data_export::show_export_submenu()?,
-            4 => {
+            4 => data_collections::show_collections_submenu()?,
+            5 => data_search::show_search_submenu()?,
+            6 => {
                 break;
             }
             _ => unreachable!(),
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+/**
+ * Controls available in a cabinet, used to check which machines can be played on it.
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CabinetProfile {
+    pub name: String,
+    pub players: u32,
+    pub buttons: u32,
+    /**
+     * Ways of the joystick (2, 4 or 8), 0 if the cabinet has no joystick.
+     */
+    pub joystick_ways: u32,
+    pub trackball: bool,
+    pub spinner: bool,
+    pub lightgun: bool,
+}
@@ -0,0 +1,121 @@
+use serde::Serialize;
+
+/**
+ * Hardware, controls and emulation details of a machine read from the MAME XML.
+ * These elements are not part of the `Machine` model provided by mame-parser.
+ */
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MachineDetails {
+    pub input: Option<Input>,
+    pub displays: Vec<Display>,
+    pub chips: Vec<Chip>,
+    pub sound: Option<Sound>,
+    pub dipswitches: Vec<DipSwitch>,
+    pub features: Vec<Feature>,
+}
+
+impl MachineDetails {
+    /**
+     * Check if no details were found for the machine.
+     */
+    pub fn is_empty(&self) -> bool {
+        self.input.is_none()
+            && self.displays.is_empty()
+            && self.chips.is_empty()
+            && self.sound.is_none()
+            && self.dipswitches.is_empty()
+            && self.features.is_empty()
+    }
+}
+
+/**
+ * Inputs of a machine (`<input>` element).
+ */
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Input {
+    pub players: u32,
+    pub coins: Option<u32>,
+    pub service: bool,
+    pub tilt: bool,
+    pub controls: Vec<Control>,
+}
+
+/**
+ * Control used by a player (`<control>` element).
+ */
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Control {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub player: Option<u32>,
+    pub buttons: Option<u32>,
+    pub req_buttons: Option<u32>,
+    pub ways: Option<String>,
+}
+
+/**
+ * Screen of a machine (`<display>` element).
+ */
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Display {
+    pub tag: Option<String>,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub rotate: u32,
+    pub flipx: bool,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub refresh: Option<f64>,
+}
+
+/**
+ * CPU or audio chip (`<chip>` element).
+ */
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Chip {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub tag: Option<String>,
+    pub name: String,
+    pub clock: Option<u64>,
+}
+
+/**
+ * Sound output (`<sound>` element).
+ */
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Sound {
+    pub channels: u32,
+}
+
+/**
+ * DIP switch with its possible values (`<dipswitch>` element).
+ */
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DipSwitch {
+    pub name: String,
+    pub tag: Option<String>,
+    pub mask: Option<String>,
+    pub values: Vec<DipValue>,
+}
+
+/**
+ * Value of a DIP switch (`<dipvalue>` element).
+ */
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DipValue {
+    pub name: String,
+    pub value: Option<String>,
+    pub is_default: bool,
+}
+
+/**
+ * Emulation issue of a machine (`<feature>` element).
+ */
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Feature {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub status: Option<String>,
+    pub overall: Option<String>,
+}
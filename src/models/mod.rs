@@ -0,0 +1,7 @@
+/**
+ * This file is used to export all the models not provided by mame-parser
+ */
+pub mod cabinet_profile;
+pub mod machine_details;
+pub mod player_count;
+pub mod stats_table;
@@ -0,0 +1,17 @@
+use serde::Serialize;
+
+/**
+ * Player information of a machine parsed from the free text of nplayers.ini.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PlayerCount {
+    pub max_players: u32,
+    /**
+     * Maximum number of players playing at the same time.
+     */
+    pub max_simultaneous: u32,
+    pub simultaneous: bool,
+    pub alternating: bool,
+    pub coop: bool,
+    pub versus: bool,
+}
@@ -0,0 +1,21 @@
+/**
+ * Table of a statistics view, kept apart from its rendering so it can be shown in the
+ * terminal or written to a report.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct StatsTable {
+    pub title: String,
+    pub headers: Vec<String>,
+    /**
+     * Values of each row, with the numbers written without thousands separators.
+     */
+    pub rows: Vec<Vec<String>>,
+    /**
+     * Row with the totals of the columns, shown after the other rows.
+     */
+    pub totals: Option<Vec<String>>,
+    /**
+     * Positions of the columns holding numbers, right aligned when rendered.
+     */
+    pub numeric_columns: Vec<usize>,
+}
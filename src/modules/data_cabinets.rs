@@ -0,0 +1,445 @@
+use crate::helpers::file_helper::{get_safe_file_name, validate_file_name};
+use crate::helpers::players_helper::get_player_count;
+use crate::helpers::ui_helper::{icons::*, print_message, println_message, show_section};
+use crate::models::cabinet_profile::CabinetProfile;
+use crate::models::machine_details::MachineDetails;
+use crate::modules::data_filtering::keep_machines;
+use crate::{MACHINES, MACHINE_DETAILS};
+use dialoguer::{console::style, theme::ColorfulTheme, Confirm, Input, Select};
+use mame_parser::models::Machine;
+use prettytable::{row, Cell, Row, Table};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufReader, ErrorKind};
+use std::path::{Path, PathBuf};
+
+/**
+ * Maximum number of near-misses shown in the report table.
+ */
+const MAX_NEAR_MISSES: usize = 20;
+
+/**
+ * Machine that fails to fit the cabinet profile by a single requirement.
+ */
+struct NearMiss {
+    name: String,
+    description: String,
+    missing: String,
+}
+
+/**
+ * Keep only the machines whose input requirements fit a cabinet profile.
+ */
+pub fn filter_by_cabinet_profile() -> Result<(), Box<dyn Error>> {
+    show_section("Keep machines fitting a cabinet profile");
+
+    let profile = match select_cabinet_profile()? {
+        Some(profile) => profile,
+        None => {
+            println!();
+            return Ok(());
+        }
+    };
+
+    // Requirements not met by each machine
+    let (missing_requirements, near_misses) = {
+        let machines = MACHINES.lock().unwrap();
+
+        if machines.is_empty() {
+            let message = format!(
+                "Error: {}",
+                "No machines data loaded, please read the data first."
+            );
+            println_message(&message, ERROR);
+            println!();
+            return Ok(());
+        }
+
+        let machines_details = MACHINE_DETAILS.lock().unwrap();
+
+        let mut missing_requirements: HashMap<String, Vec<String>> = HashMap::new();
+        let mut near_misses: Vec<NearMiss> = Vec::new();
+
+        for machine in machines.values() {
+            let missing =
+                get_missing_requirements(&profile, machine, machines_details.get(&machine.name));
+            if missing.len() == 1 {
+                near_misses.push(NearMiss {
+                    name: machine.name.clone(),
+                    description: machine.description.clone().unwrap_or_default(),
+                    missing: missing[0].clone(),
+                });
+            }
+            if !missing.is_empty() {
+                missing_requirements.insert(machine.name.clone(), missing);
+            }
+        }
+
+        near_misses.sort_by(|a, b| a.name.cmp(&b.name));
+
+        (missing_requirements, near_misses)
+    };
+
+    let total_machines = MACHINES.lock().unwrap().len();
+    let message = format!(
+        "{} of {} machines fit the {} profile",
+        total_machines - missing_requirements.len(),
+        total_machines,
+        style(&profile.name).cyan()
+    );
+    println_message(&message, SUCCESS);
+
+    if !near_misses.is_empty() {
+        show_near_misses(&near_misses);
+        write_near_misses_report(&profile, &near_misses);
+    }
+
+    let remove = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!(
+            "Remove the {} machines not fitting the profile?",
+            missing_requirements.len()
+        ))
+        .default(true)
+        .interact()?;
+
+    if !remove {
+        println!();
+        return Ok(());
+    }
+
+    let rule = format!("Keep machines fitting the {} cabinet", profile.name);
+    let message = format!("Removing machines not fitting the {} cabinet", profile.name);
+    keep_machines(&rule, &message, |machine| {
+        !missing_requirements.contains_key(&machine.name)
+    })
+}
+
+/**
+ * Select one of the saved cabinet profiles or create a new one.
+ */
+fn select_cabinet_profile() -> Result<Option<CabinetProfile>, Box<dyn Error>> {
+    let profiles_path = get_profiles_path();
+    // Profiles are only saved when the existing file could be read, to not overwrite it
+    let (mut profiles, can_save) = match read_cabinet_profiles(&profiles_path) {
+        Ok(profiles) => (profiles, true),
+        Err(err) => {
+            let message = format!(
+                "Error: Unable to read {}: {}",
+                style(profiles_path.display()).cyan(),
+                err
+            );
+            println_message(&message, ERROR);
+            (Vec::new(), false)
+        }
+    };
+
+    let mut selections: Vec<String> = profiles.iter().map(describe_profile).collect();
+    selections.push("Create new profile".to_string());
+    selections.push("< Back".to_string());
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Cabinet profile")
+        .default(0)
+        .items(&selections[..])
+        .interact()
+        .unwrap();
+
+    if selection < profiles.len() {
+        return Ok(Some(profiles.swap_remove(selection)));
+    }
+    if selection == selections.len() - 1 {
+        return Ok(None);
+    }
+
+    let profile = ask_cabinet_profile()?;
+
+    if !can_save {
+        let message = format!(
+            "Error: {}",
+            "The profile is not saved, fix or remove the profiles file first."
+        );
+        println_message(&message, ERROR);
+        return Ok(Some(profile));
+    }
+
+    profiles.push(profile.clone());
+
+    match save_cabinet_profiles(&profiles_path, &profiles) {
+        Ok(()) => {
+            let message = format!("Profile saved to {}", style(profiles_path.display()).cyan());
+            println_message(&message, WRITE);
+        }
+        Err(err) => {
+            let message = format!("Error: {}", err);
+            println_message(&message, ERROR);
+        }
+    }
+
+    Ok(Some(profile))
+}
+
+/**
+ * Ask for the controls available in a new cabinet profile.
+ */
+fn ask_cabinet_profile() -> Result<CabinetProfile, Box<dyn Error>> {
+    let theme = ColorfulTheme::default();
+
+    let name: String = Input::with_theme(&theme)
+        .with_prompt("Profile name")
+        .validate_with(|name: &String| validate_file_name(name))
+        .interact_text()?;
+    let players: u32 = Input::with_theme(&theme)
+        .with_prompt("Number of players")
+        .default(2)
+        .interact_text()?;
+    let buttons: u32 = Input::with_theme(&theme)
+        .with_prompt("Buttons per player")
+        .default(6)
+        .interact_text()?;
+
+    let joysticks = &["8-way", "4-way", "2-way", "No joystick"];
+    let joystick = Select::with_theme(&theme)
+        .with_prompt("Joystick")
+        .default(0)
+        .items(&joysticks[..])
+        .interact()
+        .unwrap();
+    let joystick_ways = [8, 4, 2, 0][joystick];
+
+    let trackball = Confirm::with_theme(&theme)
+        .with_prompt("Trackball?")
+        .default(false)
+        .interact()?;
+    let spinner = Confirm::with_theme(&theme)
+        .with_prompt("Spinner?")
+        .default(false)
+        .interact()?;
+    let lightgun = Confirm::with_theme(&theme)
+        .with_prompt("Lightgun?")
+        .default(false)
+        .interact()?;
+
+    Ok(CabinetProfile {
+        name: name.trim().to_string(),
+        players,
+        buttons,
+        joystick_ways,
+        trackball,
+        spinner,
+        lightgun,
+    })
+}
+
+/**
+ * Get the requirements of a machine not met by a cabinet profile.
+ * Machines without input information in the MAME XML are checked only by the players of nplayers.ini.
+ */
+fn get_missing_requirements(
+    profile: &CabinetProfile,
+    machine: &Machine,
+    details: Option<&MachineDetails>,
+) -> Vec<String> {
+    let mut missing = Vec::new();
+
+    let input = details.and_then(|details| details.input.as_ref());
+    let players = match input {
+        Some(input) => input.players,
+        None => get_player_count(machine)
+            .map(|player_count| player_count.max_players)
+            .unwrap_or(0),
+    };
+
+    if players > profile.players {
+        let extra_players = players - profile.players;
+        missing.push(format!(
+            "needs {} more player{}",
+            extra_players,
+            if extra_players == 1 { "" } else { "s" }
+        ));
+    }
+
+    let input = match input {
+        Some(input) => input,
+        None => return missing,
+    };
+
+    let buttons = input
+        .controls
+        .iter()
+        .filter_map(|control| control.req_buttons.or(control.buttons))
+        .max()
+        .unwrap_or(0);
+    if buttons > profile.buttons {
+        let extra_buttons = buttons - profile.buttons;
+        missing.push(format!(
+            "needs {} more button{}",
+            extra_buttons,
+            if extra_buttons == 1 { "" } else { "s" }
+        ));
+    }
+
+    for control in &input.controls {
+        let requirement = match control.type_.as_str() {
+            "joy" | "doublejoy" | "triplejoy" | "stick" => {
+                let ways = parse_ways(control.ways.as_deref());
+                if profile.joystick_ways == 0 {
+                    Some("needs a joystick".to_string())
+                } else if ways > profile.joystick_ways {
+                    Some(format!("needs a {}-way joystick", ways))
+                } else {
+                    None
+                }
+            }
+            "trackball" | "mouse" if !profile.trackball => Some("needs a trackball".to_string()),
+            "dial" | "paddle" if !profile.spinner => Some("needs a spinner".to_string()),
+            "lightgun" if !profile.lightgun => Some("needs a lightgun".to_string()),
+            "trackball" | "mouse" | "dial" | "paddle" | "lightgun" | "only_buttons" | "" => None,
+            other => Some(format!("uses a {} control", other)),
+        };
+        if let Some(requirement) = requirement {
+            if !missing.contains(&requirement) {
+                missing.push(requirement);
+            }
+        }
+    }
+
+    missing
+}
+
+/**
+ * Get the number of ways of a joystick from values like "8", "strict8" or "vertical2".
+ */
+fn parse_ways(ways: Option<&str>) -> u32 {
+    ways.and_then(|ways| {
+        let digits: String = ways
+            .chars()
+            .skip_while(|c| !c.is_ascii_digit())
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        digits.parse().ok()
+    })
+    .unwrap_or(8)
+}
+
+/**
+ * Show the machines that miss a single requirement of the profile.
+ */
+fn show_near_misses(near_misses: &[NearMiss]) {
+    let mut table = Table::new();
+    table.set_titles(Row::new(vec![Cell::new("Near-misses").style_spec("H3cFg")]));
+    table.add_row(row![b -> "Machine", "Description", "Missing"]);
+    for near_miss in near_misses.iter().take(MAX_NEAR_MISSES) {
+        table.add_row(row![
+            near_miss.name,
+            near_miss.description,
+            near_miss.missing
+        ]);
+    }
+    table.printstd();
+    if near_misses.len() > MAX_NEAR_MISSES {
+        println!(
+            "... and {} more machines",
+            near_misses.len() - MAX_NEAR_MISSES
+        );
+    }
+}
+
+/**
+ * Write the near-misses report to the reports folder.
+ */
+fn write_near_misses_report(profile: &CabinetProfile, near_misses: &[NearMiss]) {
+    let file_name = format!(
+        "cabinet_near_misses_{}.csv",
+        get_safe_file_name(&profile.name.to_lowercase())
+    );
+    let report_path = Path::new("data").join("reports").join(file_name);
+
+    match write_near_misses(&report_path, near_misses) {
+        Ok(()) => {
+            let message = format!(
+                "Near-misses report written to {}",
+                style(report_path.display()).cyan()
+            );
+            println_message(&message, WRITE);
+        }
+        Err(err) => {
+            let message = format!("Error: {}", err);
+            print_message(&message, ERROR);
+        }
+    }
+}
+
+/**
+ * Write the near-misses to a CSV file.
+ */
+fn write_near_misses(report_path: &Path, near_misses: &[NearMiss]) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = report_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut writer = csv::Writer::from_path(report_path)?;
+    writer.write_record(["machine", "description", "missing"])?;
+    for near_miss in near_misses {
+        writer.write_record([&near_miss.name, &near_miss.description, &near_miss.missing])?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/**
+ * Describe the controls of a cabinet profile.
+ */
+fn describe_profile(profile: &CabinetProfile) -> String {
+    let mut controls = vec![
+        format!("{}P", profile.players),
+        format!("{} buttons", profile.buttons),
+    ];
+    if profile.joystick_ways > 0 {
+        controls.push(format!("{}-way joystick", profile.joystick_ways));
+    }
+    if profile.trackball {
+        controls.push("trackball".to_string());
+    }
+    if profile.spinner {
+        controls.push("spinner".to_string());
+    }
+    if profile.lightgun {
+        controls.push("lightgun".to_string());
+    }
+    format!("{} ({})", profile.name, controls.join(", "))
+}
+
+/**
+ * Get the path of the file storing the cabinet profiles.
+ */
+fn get_profiles_path() -> PathBuf {
+    Path::new("data").join("cabinet_profiles.json")
+}
+
+/**
+ * Read the saved cabinet profiles, an empty list if the file doesn't exist yet.
+ */
+fn read_cabinet_profiles(profiles_path: &Path) -> Result<Vec<CabinetProfile>, Box<dyn Error>> {
+    let file = match File::open(profiles_path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    Ok(serde_json::from_reader(BufReader::new(file))?)
+}
+
+/**
+ * Save the cabinet profiles.
+ */
+fn save_cabinet_profiles(
+    profiles_path: &Path,
+    profiles: &[CabinetProfile],
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = profiles_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    serde_json::to_writer_pretty(File::create(profiles_path)?, profiles)?;
+    Ok(())
+}
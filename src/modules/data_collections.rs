@@ -0,0 +1,1324 @@
+use crate::helpers::dependency_helper::get_dependencies;
+use crate::helpers::hash_helper::{hash_file, hash_files_in_paths, HashedFile, UnreadableFile};
+use crate::helpers::machine_helper::requires_samples;
+use crate::helpers::ui_helper::{icons::*, print_message, println_message, show_section};
+use crate::modules::data_diff::migrate_machine_list;
+use crate::modules::data_filtering::RESOURCE_TYPES;
+use crate::{FILTER_LOG, MACHINES};
+use dialoguer::{console::style, theme::ColorfulTheme, Input, MultiSelect, Select};
+use indicatif::{ProgressBar, ProgressStyle};
+use mame_parser::models::{Machine, Resource, Rom};
+use num_format::{Locale, ToFormattedString};
+use prettytable::{row, Cell, Row, Table};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/**
+ * Maximum number of candidate machines shown when identifying files.
+ */
+const MAX_CANDIDATES: usize = 20;
+
+/**
+ * Layout used when rebuilding ROM sets.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RebuildMode {
+    Merged,
+    Split,
+    NonMerged,
+}
+
+impl RebuildMode {
+    /**
+     * Get the name of the layout shown to the user.
+     */
+    fn as_str(&self) -> &'static str {
+        match self {
+            RebuildMode::Merged => "merged",
+            RebuildMode::Split => "split",
+            RebuildMode::NonMerged => "non-merged",
+        }
+    }
+}
+
+/**
+ * File that has to be stored inside a rebuilt set.
+ */
+struct RebuildFile {
+    name: String,
+    size: u64,
+    crc: String,
+    sha1: Option<String>,
+}
+
+/**
+ * Zip file to be rebuilt with all the files it should contain.
+ */
+struct RebuildSet {
+    name: String,
+    files: Vec<RebuildFile>,
+}
+
+/**
+ * Machine that contains some of the identified files.
+ */
+struct IdentifyCandidate<'a> {
+    machine: &'a Machine,
+    matched: HashSet<&'a str>,
+    total: usize,
+}
+
+/**
+ * Archive, CHD or sample file required by the current machines.
+ */
+struct RequiredFile {
+    path: String,
+    type_: &'static str,
+    machines: usize,
+    /**
+     * Estimated size in bytes, None when it can't be computed from the data.
+     */
+    size: Option<u64>,
+}
+
+/**
+ * Result of the media audit for a resource type.
+ */
+#[derive(Default)]
+struct MediaAuditResult {
+    expected: usize,
+    valid: usize,
+    missing: Vec<(String, String)>,
+    corrupted: Vec<(String, String)>,
+    /**
+     * Files of the machines removed by the filters, with the machine they belong to.
+     */
+    filtered: Vec<(String, String)>,
+    extra: Vec<String>,
+}
+
+/**
+ * Show the collections submenu.
+ */
+pub fn show_collections_submenu() -> Result<(), Box<dyn Error>> {
+    loop {
+        let selections = &[
+            "Rebuild ROM sets",
+            "Identify unknown ROM files",
+            "Audit media files",
+            "Migrate a machine list",
+            "< Back",
+        ];
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .default(0)
+            .items(&selections[..])
+            .interact()
+            .unwrap();
+
+        match selection {
+            0 => rebuild_rom_sets()?,
+            1 => identify_rom_files()?,
+            2 => audit_media_files()?,
+            3 => migrate_machine_list()?,
+            4 => {
+                break;
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    Ok(())
+}
+
+/**
+ * Rebuild the ROM sets of the current machines from the files found in the source folders.
+ */
+fn rebuild_rom_sets() -> Result<(), Box<dyn Error>> {
+    show_section("Rebuild ROM sets");
+
+    let machines = MACHINES.lock().unwrap();
+
+    if machines.is_empty() {
+        let message = format!(
+            "Error: {}",
+            "No machines data loaded, please read the data first."
+        );
+        println_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let source_paths = ask_source_paths()?;
+
+    let modes = [
+        RebuildMode::Merged,
+        RebuildMode::Split,
+        RebuildMode::NonMerged,
+    ];
+    let mode_selections = &["Merged", "Split", "Non-merged"];
+    let mode = modes[Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Set layout")
+        .default(0)
+        .items(&mode_selections[..])
+        .interact()
+        .unwrap()];
+
+    let output_path: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Output folder")
+        .default(format!("data/rebuild/{}", mode.as_str()))
+        .interact_text()?;
+
+    let run_selections = &["Dry run (show what would be written)", "Write zip files"];
+    let dry_run = Select::with_theme(&ColorfulTheme::default())
+        .default(0)
+        .items(&run_selections[..])
+        .interact()
+        .unwrap()
+        == 0;
+
+    let time = std::time::Instant::now();
+
+    println_message("Hashing source files", WRITE);
+    let (source_files, unreadable_files) = match hash_source_files(&source_paths) {
+        Ok(files) => files,
+        Err(err) => {
+            let message = format!("Error: {}", err);
+            print_message(&message, ERROR);
+            println!();
+            return Ok(());
+        }
+    };
+
+    let rebuild_sets = get_rebuild_sets(&machines, mode);
+    let (matched_sets, used_files) = match_rebuild_sets(&rebuild_sets, &source_files);
+
+    let sets_to_write: Vec<&(&RebuildSet, Vec<(String, usize)>)> = matched_sets
+        .iter()
+        .filter(|(_, files)| !files.is_empty())
+        .collect();
+    let complete_sets = sets_to_write
+        .iter()
+        .filter(|(set, files)| set.files.len() == files.len())
+        .count();
+    let unmatched_files: Vec<&HashedFile> = source_files
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !used_files.contains(index))
+        .map(|(_, file)| file)
+        .collect();
+
+    if dry_run {
+        let mut table = Table::new();
+        table.set_titles(Row::new(vec![
+            Cell::new("Sets that would be written").style_spec("H4cFg")
+        ]));
+        table.add_row(row![b -> "#", "Set", "Files", "Status"]);
+        for (counter, (set, files)) in sets_to_write.iter().enumerate() {
+            let status = if set.files.len() == files.len() {
+                "Complete"
+            } else {
+                "Incomplete"
+            };
+            table.add_row(row![
+                counter + 1,
+                format!("{}.zip", set.name),
+                r -> format!("{}/{}", files.len(), set.files.len()),
+                status
+            ]);
+        }
+        table.printstd();
+        println!();
+    } else {
+        let message = format!("Writing sets to {}", style(&output_path).cyan());
+        println_message(&message, WRITE);
+
+        if let Err(err) = write_rebuild_sets(Path::new(&output_path), &sets_to_write, &source_files)
+        {
+            let message = format!("Error: {}", err);
+            print_message(&message, ERROR);
+            println!();
+            return Ok(());
+        }
+    }
+
+    if !unmatched_files.is_empty() || !unreadable_files.is_empty() {
+        let mut table = Table::new();
+        table.set_titles(Row::new(vec![
+            Cell::new("Unmatched source files").style_spec("H3cFg")
+        ]));
+        table.add_row(row![b -> "#", "File", "CRC"]);
+        for (counter, file) in unmatched_files.iter().enumerate() {
+            table.add_row(row![counter + 1, file.display_name(), file.hashes.crc]);
+        }
+        for (counter, file) in unreadable_files.iter().enumerate() {
+            table.add_row(row![
+                unmatched_files.len() + counter + 1,
+                file.name,
+                format!("Unreadable: {}", file.error)
+            ]);
+        }
+        table.printstd();
+        println!();
+    }
+
+    let rounded_secs = (time.elapsed().as_secs_f32() * 10.0).round() / 10.0;
+    let message = format!(
+        "{} {} sets {} ({} complete, {} incomplete), {} sets without files, {} unmatched files in {}s",
+        sets_to_write.len().to_formatted_string(&Locale::en),
+        mode.as_str(),
+        if dry_run { "would be written" } else { "written" },
+        complete_sets.to_formatted_string(&Locale::en),
+        (sets_to_write.len() - complete_sets).to_formatted_string(&Locale::en),
+        (rebuild_sets.len() - sets_to_write.len()).to_formatted_string(&Locale::en),
+        (unmatched_files.len() + unreadable_files.len()).to_formatted_string(&Locale::en),
+        rounded_secs
+    );
+    print_message(&message, SUCCESS);
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Identify the files in a folder or archive by looking up their hashes in the ROMs and disks of the machines.
+ */
+fn identify_rom_files() -> Result<(), Box<dyn Error>> {
+    show_section("Identify unknown ROM files");
+
+    let machines = MACHINES.lock().unwrap();
+
+    if machines.is_empty() {
+        let message = format!(
+            "Error: {}",
+            "No machines data loaded, please read the data first."
+        );
+        println_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let path: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Folder or archive to identify")
+        .interact_text()?;
+
+    let time = std::time::Instant::now();
+
+    println_message("Hashing files", WRITE);
+    let (files, unreadable_files) = match hash_source_files(&[PathBuf::from(path.trim())]) {
+        Ok(files) => files,
+        Err(err) => {
+            let message = format!("Error: {}", err);
+            print_message(&message, ERROR);
+            println!();
+            return Ok(());
+        }
+    };
+
+    // Index every ROM and disk by its hashes
+    let mut crc_index: HashMap<String, Vec<(&Machine, &str, u64)>> = HashMap::new();
+    let mut sha1_index: HashMap<String, Vec<(&Machine, &str)>> = HashMap::new();
+    for machine in machines.values() {
+        for rom in machine.roms.iter().filter(|rom| is_dumped(rom)) {
+            if let Some(sha1) = &rom.sha1 {
+                sha1_index
+                    .entry(sha1.to_lowercase())
+                    .or_default()
+                    .push((machine, &rom.name));
+            }
+            if let Some(crc) = &rom.crc {
+                crc_index
+                    .entry(crc.to_lowercase())
+                    .or_default()
+                    .push((machine, &rom.name, rom.size));
+            }
+        }
+        for disk in &machine.disks {
+            if let Some(sha1) = &disk.sha1 {
+                sha1_index
+                    .entry(sha1.to_lowercase())
+                    .or_default()
+                    .push((machine, &disk.name));
+            }
+        }
+    }
+
+    let mut candidates: HashMap<&str, IdentifyCandidate> = HashMap::new();
+    let mut file_matches: Vec<(&HashedFile, Vec<(&Machine, &str)>)> = Vec::new();
+
+    for file in &files {
+        let mut matches: Vec<(&Machine, &str)> = sha1_index
+            .get(&file.hashes.sha1)
+            .cloned()
+            .unwrap_or_default();
+        if matches.is_empty() {
+            matches = crc_index
+                .get(&file.hashes.crc)
+                .map(|entries| {
+                    entries
+                        .iter()
+                        .filter(|(_, _, size)| *size == file.hashes.size)
+                        .map(|(machine, name, _)| (*machine, *name))
+                        .collect()
+                })
+                .unwrap_or_default();
+        }
+
+        for (machine, name) in &matches {
+            candidates
+                .entry(&machine.name)
+                .or_insert_with(|| IdentifyCandidate {
+                    machine,
+                    matched: HashSet::new(),
+                    total: machine.roms.iter().filter(|rom| is_dumped(rom)).count()
+                        + machine.disks.iter().filter(|d| d.sha1.is_some()).count(),
+                })
+                .matched
+                .insert(name);
+        }
+        file_matches.push((file, matches));
+    }
+
+    let mut candidates: Vec<IdentifyCandidate> = candidates.into_values().collect();
+    candidates.sort_by(|a, b| {
+        b.matched
+            .len()
+            .cmp(&a.matched.len())
+            .then_with(|| {
+                let a_ratio = a.matched.len() as f32 / a.total.max(1) as f32;
+                let b_ratio = b.matched.len() as f32 / b.total.max(1) as f32;
+                b_ratio.total_cmp(&a_ratio)
+            })
+            .then_with(|| a.machine.name.cmp(&b.machine.name))
+    });
+
+    let mut table = Table::new();
+    table.set_titles(Row::new(vec![Cell::new("Files").style_spec("H4cFg")]));
+    table.add_row(row![b -> "File", "CRC", "Machines", "Example"]);
+    for (file, matches) in &file_matches {
+        let example = matches
+            .first()
+            .map(|(machine, name)| format!("{} ({})", machine.name, name))
+            .unwrap_or_else(|| "Unknown".to_string());
+        table.add_row(row![
+            file.display_name(),
+            file.hashes.crc,
+            r -> matches.len().to_formatted_string(&Locale::en),
+            example
+        ]);
+    }
+    for file in &unreadable_files {
+        table.add_row(row![
+            file.name,
+            "",
+            r -> 0,
+            format!("Unreadable: {}", file.error)
+        ]);
+    }
+    table.printstd();
+    println!();
+
+    if !candidates.is_empty() {
+        let mut table = Table::new();
+        table.set_titles(Row::new(vec![
+            Cell::new("Candidate machines").style_spec("H6cFg")
+        ]));
+        table.add_row(row![b -> "#", "Machine", "Description", "Matched", "Total", "%"]);
+        for (counter, candidate) in candidates.iter().take(MAX_CANDIDATES).enumerate() {
+            let percentage = candidate.matched.len() as f32 * 100.0 / candidate.total.max(1) as f32;
+            table.add_row(row![
+                counter + 1,
+                candidate.machine.name,
+                candidate.machine.description.as_deref().unwrap_or_default(),
+                r -> candidate.matched.len(),
+                r -> candidate.total,
+                r -> format!("{:.1}", percentage)
+            ]);
+        }
+        table.printstd();
+        if candidates.len() > MAX_CANDIDATES {
+            println!(
+                "... and {} more candidates",
+                candidates.len() - MAX_CANDIDATES
+            );
+        }
+        println!();
+    }
+
+    let identified_files = file_matches
+        .iter()
+        .filter(|(_, matches)| !matches.is_empty())
+        .count();
+    let rounded_secs = (time.elapsed().as_secs_f32() * 10.0).round() / 10.0;
+    let message = format!(
+        "{} of {} files identified, {} candidate machines in {}s",
+        identified_files.to_formatted_string(&Locale::en),
+        (files.len() + unreadable_files.len()).to_formatted_string(&Locale::en),
+        candidates.len().to_formatted_string(&Locale::en),
+        rounded_secs
+    );
+    print_message(&message, SUCCESS);
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Audit the local media folders against the resources of the current machines.
+ */
+fn audit_media_files() -> Result<(), Box<dyn Error>> {
+    show_section("Audit media files");
+
+    let machines = MACHINES.lock().unwrap();
+
+    if machines.is_empty() {
+        let message = format!(
+            "Error: {}",
+            "No machines data loaded, please read the data first."
+        );
+        println_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let media_path: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Media folder (containing one folder per resource type)")
+        .default("media".to_string())
+        .interact_text()?;
+    let media_path = PathBuf::from(media_path.trim());
+
+    // Preselect the resource types with a folder in the media path
+    let defaults: Vec<bool> = RESOURCE_TYPES
+        .iter()
+        .map(|resource_type| media_path.join(resource_type).is_dir())
+        .collect();
+    let selected = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Resource types to audit (space to select, enter to confirm)")
+        .items(RESOURCE_TYPES)
+        .defaults(&defaults)
+        .interact()
+        .unwrap();
+
+    if selected.is_empty() {
+        println!();
+        return Ok(());
+    }
+
+    let time = std::time::Instant::now();
+
+    let filter_log = FILTER_LOG.lock().unwrap();
+
+    let mut expected_resources: HashMap<&str, Vec<(&Machine, &Resource)>> = HashMap::new();
+    for machine in machines.values() {
+        for resource in &machine.resources {
+            expected_resources
+                .entry(resource.type_.as_str())
+                .or_default()
+                .push((machine, resource));
+        }
+    }
+
+    let total_files: usize = selected
+        .iter()
+        .filter_map(|&i| expected_resources.get(RESOURCE_TYPES[i]))
+        .map(|resources| resources.len())
+        .sum();
+
+    println_message("Checking media files", WRITE);
+
+    let progress_bar = ProgressBar::new(total_files as u64);
+    progress_bar.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:20.cyan/blue}] {pos}/{len} ({eta}) {msg}")
+            .progress_chars("#>-"),
+    );
+
+    let mut results: Vec<(&str, MediaAuditResult)> = Vec::new();
+
+    for &index in &selected {
+        let resource_type = RESOURCE_TYPES[index];
+        progress_bar.set_message(resource_type);
+
+        let mut result = MediaAuditResult::default();
+        let mut expected_paths: HashSet<PathBuf> = HashSet::new();
+
+        // Files of the machines removed by the filters, reported apart from the extra files
+        let filtered_paths: HashMap<PathBuf, &str> = filter_log
+            .iter()
+            .flat_map(|entry| {
+                entry
+                    .machine
+                    .resources
+                    .iter()
+                    .filter(|resource| resource.type_ == resource_type)
+                    .map(|resource| {
+                        (
+                            media_path.join(resource.name.replace('\\', "/")),
+                            entry.machine.name.as_str(),
+                        )
+                    })
+            })
+            .collect();
+
+        for (machine, resource) in expected_resources
+            .get(resource_type)
+            .map(|resources| resources.as_slice())
+            .unwrap_or_default()
+        {
+            let path = media_path.join(resource.name.replace('\\', "/"));
+            result.expected += 1;
+
+            match fs::metadata(&path) {
+                Err(_) => result
+                    .missing
+                    .push((machine.name.clone(), resource.name.clone())),
+                Ok(metadata) => {
+                    // Files that cannot be read are counted as corrupted
+                    let is_valid = metadata.len() == resource.size
+                        && hash_file(&path).is_ok_and(|hashes| {
+                            hashes.crc.eq_ignore_ascii_case(&resource.crc)
+                                && hashes.sha1.eq_ignore_ascii_case(&resource.sha1)
+                        });
+                    if is_valid {
+                        result.valid += 1;
+                    } else {
+                        result
+                            .corrupted
+                            .push((machine.name.clone(), resource.name.clone()));
+                    }
+                }
+            }
+
+            expected_paths.insert(path);
+            progress_bar.inc(1);
+        }
+
+        for entry in WalkDir::new(media_path.join(resource_type))
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            if !entry.path().is_file() || expected_paths.contains(entry.path()) {
+                continue;
+            }
+            match filtered_paths.get(entry.path()) {
+                Some(machine) => result
+                    .filtered
+                    .push((machine.to_string(), entry.path().display().to_string())),
+                None => result.extra.push(entry.path().display().to_string()),
+            }
+        }
+
+        results.push((resource_type, result));
+    }
+
+    progress_bar.finish_and_clear();
+
+    let mut table = Table::new();
+    table.set_titles(Row::new(vec![Cell::new("Media audit").style_spec("H8cFg")]));
+    table.add_row(
+        row![b -> "Type", "Expected", "Valid", "Missing", "Corrupted", "Filtered", "Extra", "Coverage"],
+    );
+    for (resource_type, result) in &results {
+        let coverage = if result.expected > 0 {
+            format!(
+                "{:.1}%",
+                result.valid as f32 * 100.0 / result.expected as f32
+            )
+        } else {
+            "-".to_string()
+        };
+        table.add_row(row![
+            resource_type,
+            r -> result.expected.to_formatted_string(&Locale::en),
+            r -> result.valid.to_formatted_string(&Locale::en),
+            r -> result.missing.len().to_formatted_string(&Locale::en),
+            r -> result.corrupted.len().to_formatted_string(&Locale::en),
+            r -> result.filtered.len().to_formatted_string(&Locale::en),
+            r -> result.extra.len().to_formatted_string(&Locale::en),
+            r -> coverage
+        ]);
+    }
+    table.printstd();
+    println!();
+
+    let report_path = Path::new("data").join("reports").join("media_audit.csv");
+    if let Err(err) = write_media_audit_report(&report_path, &results) {
+        let message = format!("Error: {}", err);
+        print_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let rounded_secs = (time.elapsed().as_secs_f32() * 10.0).round() / 10.0;
+    let message = format!(
+        "Media audited in {}s, details written to {}",
+        rounded_secs,
+        style(report_path.display()).cyan()
+    );
+    print_message(&message, SUCCESS);
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Write the missing, corrupted and extra media files to a CSV file.
+ */
+fn write_media_audit_report(
+    report_path: &Path,
+    results: &[(&str, MediaAuditResult)],
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = report_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut writer = csv::Writer::from_path(report_path)?;
+    writer.write_record(["type", "status", "machine", "file"])?;
+
+    for (resource_type, result) in results {
+        for (machine, file) in &result.missing {
+            writer.write_record([resource_type, "missing", machine, file])?;
+        }
+        for (machine, file) in &result.corrupted {
+            writer.write_record([resource_type, "corrupted", machine, file])?;
+        }
+        for (machine, file) in &result.filtered {
+            writer.write_record([resource_type, "filtered", machine, file])?;
+        }
+        for file in &result.extra {
+            writer.write_record([resource_type, "extra", "", file])?;
+        }
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+/**
+ * Export the list of archives, CHDs and samples needed to run the current machines.
+ */
+pub fn export_required_files() -> Result<(), Box<dyn Error>> {
+    show_section("Export required files list");
+
+    let machines = MACHINES.lock().unwrap();
+
+    if machines.is_empty() {
+        let message = format!(
+            "Error: {}",
+            "No machines data loaded, please read the data first."
+        );
+        println_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let modes = [
+        RebuildMode::Merged,
+        RebuildMode::Split,
+        RebuildMode::NonMerged,
+    ];
+    let mode_selections = &["Merged", "Split", "Non-merged"];
+    let mode = modes[Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Set layout")
+        .default(0)
+        .items(&mode_selections[..])
+        .interact()
+        .unwrap()];
+
+    // Machines removed by filters can still be needed as parents, BIOS or devices
+    let filter_log = FILTER_LOG.lock().unwrap();
+    let mut all_machines: HashMap<&str, &Machine> = filter_log
+        .iter()
+        .map(|entry| (entry.machine.name.as_str(), &entry.machine))
+        .collect();
+    all_machines.extend(
+        machines
+            .iter()
+            .map(|(name, machine)| (name.as_str(), machine)),
+    );
+
+    let mut table = Table::new();
+    table.set_titles(Row::new(vec![
+        Cell::new("Required files").style_spec("H4cFg")
+    ]));
+    table.add_row(row![b -> "Layout", "Archives", "Unknown archives", "Estimated size"]);
+
+    let mut selected_files: Vec<RequiredFile> = Vec::new();
+    for layout in modes {
+        let archives = get_required_archives(&machines, &all_machines, layout);
+        let unknown = archives.iter().filter(|file| file.size.is_none()).count();
+        let total_size: u64 = archives.iter().filter_map(|file| file.size).sum();
+        table.add_row(row![
+            layout.as_str(),
+            r -> archives.len().to_formatted_string(&Locale::en),
+            r -> unknown.to_formatted_string(&Locale::en),
+            r -> format_size(total_size)
+        ]);
+        if layout == mode {
+            selected_files = archives;
+        }
+    }
+    table.printstd();
+
+    selected_files.extend(get_required_disks_and_samples(&machines, mode));
+
+    let file_path = Path::new("data")
+        .join("export")
+        .join(format!("required_files_{}.csv", mode.as_str()));
+
+    let message = format!("Writing {}", style(file_path.display()).cyan());
+    println_message(&message, WRITE);
+
+    if let Err(err) = write_required_files(&file_path, &selected_files) {
+        let message = format!("Error: {}", err);
+        print_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let message = format!("{} required files exported", selected_files.len());
+    print_message(&message, SUCCESS);
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Get the ROM archives needed by the current machines in the given layout, with their estimated size.
+ * Non-merged sets need their own archive and the devices, split sets also the parents and BIOS,
+ * and merged sets the archive of the parent instead of the clone.
+ */
+fn get_required_archives(
+    machines: &HashMap<String, Machine>,
+    all_machines: &HashMap<&str, &Machine>,
+    mode: RebuildMode,
+) -> Vec<RequiredFile> {
+    let mut machines_by_archive: HashMap<String, usize> = HashMap::new();
+    let mut required_machines: HashSet<&str> = HashSet::new();
+
+    for machine in machines.values() {
+        let mut archives: HashSet<String> = HashSet::new();
+        let mut visited: HashSet<&str> = HashSet::new();
+        let mut pending: Vec<&str> = vec![machine.name.as_str()];
+
+        while let Some(name) = pending.pop() {
+            if !visited.insert(name) {
+                continue;
+            }
+            let known_machine = all_machines.get(name);
+            let archive = match (mode, known_machine) {
+                (RebuildMode::Merged, Some(known_machine)) => {
+                    known_machine.clone_of.as_deref().unwrap_or(name)
+                }
+                _ => name,
+            };
+            archives.insert(archive.to_string());
+
+            if let Some(known_machine) = known_machine {
+                for (dependency, kind) in get_dependencies(known_machine) {
+                    if mode != RebuildMode::NonMerged || kind == "device" {
+                        pending.push(dependency);
+                    }
+                }
+            }
+        }
+
+        required_machines.extend(visited);
+        for archive in archives {
+            *machines_by_archive.entry(archive).or_insert(0) += 1;
+        }
+    }
+
+    let rebuild_machines: HashMap<String, Machine> = required_machines
+        .iter()
+        .filter_map(|name| all_machines.get(name))
+        .map(|machine| (machine.name.clone(), (*machine).clone()))
+        .collect();
+    let set_sizes: HashMap<String, u64> = get_rebuild_sets(&rebuild_machines, mode)
+        .into_iter()
+        .map(|set| (set.name, set.files.iter().map(|file| file.size).sum()))
+        .collect();
+
+    let mut archives: Vec<RequiredFile> = machines_by_archive
+        .into_iter()
+        // Known machines without dumped ROMs don't need an archive
+        .filter(|(archive, _)| {
+            set_sizes.contains_key(archive) || !all_machines.contains_key(archive.as_str())
+        })
+        .map(|(archive, machines)| RequiredFile {
+            size: set_sizes.get(&archive).copied(),
+            path: format!("{}.zip", archive),
+            type_: "rom",
+            machines,
+        })
+        .collect();
+    archives.sort_by(|a, b| a.path.cmp(&b.path));
+
+    archives
+}
+
+/**
+ * Get the CHD and sample files needed by the current machines in the given layout.
+ * In split and merged layouts the CHDs with a merge attribute are stored in the parent folder.
+ */
+fn get_required_disks_and_samples(
+    machines: &HashMap<String, Machine>,
+    mode: RebuildMode,
+) -> Vec<RequiredFile> {
+    let mut machines_by_file: HashMap<(String, &'static str), usize> = HashMap::new();
+
+    for machine in machines.values() {
+        for disk in &machine.disks {
+            if disk.status.as_deref() == Some("nodump") {
+                continue;
+            }
+            let path = match (&disk.merge, &machine.clone_of) {
+                (Some(merge), Some(parent)) if mode != RebuildMode::NonMerged => {
+                    format!("{}/{}.chd", parent, merge)
+                }
+                _ => format!("{}/{}.chd", machine.name, disk.name),
+            };
+            *machines_by_file.entry((path, "chd")).or_insert(0) += 1;
+        }
+
+        if requires_samples(machine) {
+            let sample_set = machine.sample_of.as_deref().unwrap_or(&machine.name);
+            let path = format!("samples/{}.zip", sample_set);
+            *machines_by_file.entry((path, "sample")).or_insert(0) += 1;
+        }
+    }
+
+    let mut files: Vec<RequiredFile> = machines_by_file
+        .into_iter()
+        .map(|((path, type_), machines)| RequiredFile {
+            path,
+            type_,
+            machines,
+            size: None,
+        })
+        .collect();
+    files.sort_by(|a, b| a.type_.cmp(b.type_).then(a.path.cmp(&b.path)));
+
+    files
+}
+
+/**
+ * Write the required files to a CSV file.
+ */
+fn write_required_files(file_path: &Path, files: &[RequiredFile]) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut writer = csv::Writer::from_path(file_path)?;
+    writer.write_record(["file", "type", "machines", "size"])?;
+    for file in files {
+        writer.write_record([
+            file.path.as_str(),
+            file.type_,
+            &file.machines.to_string(),
+            &file.size.map(|size| size.to_string()).unwrap_or_default(),
+        ])?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/**
+ * Format a size in bytes as MB or GB.
+ */
+fn format_size(bytes: u64) -> String {
+    let megabytes = bytes as f64 / (1024.0 * 1024.0);
+    if megabytes >= 1024.0 {
+        format!("{:.1} GB", megabytes / 1024.0)
+    } else {
+        format!("{:.1} MB", megabytes)
+    }
+}
+
+/**
+ * Ask for the folders where the ROM files are located.
+ */
+fn ask_source_paths() -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let source_paths: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Source folders (separate multiple folders with ;)")
+        .interact_text()?;
+
+    Ok(source_paths
+        .split(';')
+        .map(|path| path.trim())
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+/**
+ * Hash all the files in the source paths showing a spinner while working, returning apart the
+ * files that cannot be read.
+ */
+fn hash_source_files(
+    source_paths: &[PathBuf],
+) -> Result<(Vec<HashedFile>, Vec<UnreadableFile>), Box<dyn Error>> {
+    let progress_bar = ProgressBar::new_spinner();
+    progress_bar.set_style(
+        ProgressStyle::default_spinner()
+            .template("{spinner:.green} [{elapsed_precise}] {pos} files {msg}"),
+    );
+
+    let source_files = hash_files_in_paths(source_paths, &progress_bar);
+
+    progress_bar.finish_and_clear();
+
+    source_files
+}
+
+/**
+ * Get the sets to rebuild for the given machines and layout.
+ * In split and merged layouts the files with a merge attribute are left to the parent or BIOS set.
+ */
+fn get_rebuild_sets(machines: &HashMap<String, Machine>, mode: RebuildMode) -> Vec<RebuildSet> {
+    let mut sets: Vec<RebuildSet> = Vec::new();
+
+    match mode {
+        RebuildMode::NonMerged | RebuildMode::Split => {
+            for machine in machines.values() {
+                let mut set = RebuildSet {
+                    name: machine.name.clone(),
+                    files: Vec::new(),
+                };
+                for rom in machine.roms.iter().filter(|rom| is_dumped(rom)) {
+                    if mode == RebuildMode::Split && merges_into_set(machine, rom, machines) {
+                        continue;
+                    }
+                    add_rebuild_file(&mut set, &rom.name, rom, None);
+                }
+                sets.push(set);
+            }
+        }
+        RebuildMode::Merged => {
+            // Group the clones with their parents
+            let mut families: HashMap<&str, Vec<&Machine>> = HashMap::new();
+            for machine in machines.values() {
+                let parent = machine.clone_of.as_deref().unwrap_or(&machine.name);
+                families.entry(parent).or_default().push(machine);
+            }
+
+            for (parent_name, mut members) in families {
+                let parent = machines.get(parent_name);
+                let mut set = RebuildSet {
+                    name: parent_name.to_string(),
+                    files: Vec::new(),
+                };
+
+                if let Some(parent) = parent {
+                    for rom in parent.roms.iter().filter(|rom| is_dumped(rom)) {
+                        if !merges_into_set(parent, rom, machines) {
+                            add_rebuild_file(&mut set, &rom.name, rom, None);
+                        }
+                    }
+                }
+
+                members.sort_by(|a, b| a.name.cmp(&b.name));
+                for clone in members.iter().filter(|m| m.name != parent_name) {
+                    for rom in clone.roms.iter().filter(|rom| is_dumped(rom)) {
+                        match &rom.merge {
+                            None => add_rebuild_file(&mut set, &rom.name, rom, Some(&clone.name)),
+                            // The set holding the file is not in the current set, keep it through the clone
+                            Some(merge)
+                                if parent.is_none() && !merges_into_set(clone, rom, machines) =>
+                            {
+                                add_rebuild_file(&mut set, merge, rom, None)
+                            }
+                            Some(_) => {}
+                        }
+                    }
+                }
+
+                sets.push(set);
+            }
+        }
+    }
+
+    sets.retain(|set| !set.files.is_empty());
+    sets.sort_by(|a, b| a.name.cmp(&b.name));
+    sets
+}
+
+/**
+ * Check if the ROM is merged into a parent (`clone_of`) or BIOS (`rom_of`) in the current set,
+ * looking for the file by its merge name and CRC. When that set has been filtered out, the
+ * merged file goes in the machine set.
+ */
+fn merges_into_set(machine: &Machine, rom: &Rom, machines: &HashMap<String, Machine>) -> bool {
+    let merge = match &rom.merge {
+        Some(merge) => merge,
+        None => return false,
+    };
+
+    machine
+        .clone_of
+        .iter()
+        .chain(machine.rom_of.iter())
+        .filter_map(|name| machines.get(name))
+        .any(|target| {
+            target.roms.iter().any(|target_rom| {
+                target_rom.name == *merge
+                    && target_rom.crc.as_deref().map(str::to_lowercase)
+                        == rom.crc.as_deref().map(str::to_lowercase)
+            })
+        })
+}
+
+/**
+ * Check if the ROM has been dumped and can be looked up by its hashes.
+ */
+fn is_dumped(rom: &Rom) -> bool {
+    rom.crc.is_some() && rom.status.as_deref() != Some("nodump")
+}
+
+/**
+ * Add a file to a set skipping duplicates.
+ * Files with the same name but different content are stored in a folder named after the clone.
+ */
+fn add_rebuild_file(set: &mut RebuildSet, name: &str, rom: &Rom, clone_name: Option<&str>) {
+    let crc = rom.crc.as_deref().unwrap_or_default().to_lowercase();
+
+    let mut file_name = name.to_string();
+    if let Some(existing) = set.files.iter().find(|f| f.name == file_name) {
+        if existing.crc == crc {
+            return;
+        }
+        match clone_name {
+            Some(clone_name) => file_name = format!("{}/{}", clone_name, name),
+            None => return,
+        }
+    }
+
+    set.files.push(RebuildFile {
+        name: file_name,
+        size: rom.size,
+        crc,
+        sha1: rom.sha1.as_ref().map(|sha1| sha1.to_lowercase()),
+    });
+}
+
+/**
+ * Match the files of each set against the source files.
+ * Returns every set with the matched file names and source indexes, and the indexes of the used source files.
+ */
+#[allow(clippy::type_complexity)]
+fn match_rebuild_sets<'a>(
+    sets: &'a [RebuildSet],
+    source_files: &[HashedFile],
+) -> (Vec<(&'a RebuildSet, Vec<(String, usize)>)>, HashSet<usize>) {
+    let mut index: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (position, file) in source_files.iter().enumerate() {
+        index.entry(&file.hashes.crc).or_default().push(position);
+    }
+
+    let mut used_files = HashSet::new();
+    let matched_sets = sets
+        .iter()
+        .map(|set| {
+            let matched_files = set
+                .files
+                .iter()
+                .filter_map(|file| {
+                    let position = index.get(file.crc.as_str())?.iter().copied().find(|&p| {
+                        let hashes = &source_files[p].hashes;
+                        hashes.size == file.size
+                            && file.sha1.as_ref().is_none_or(|sha1| *sha1 == hashes.sha1)
+                    })?;
+                    used_files.insert(position);
+                    Some((file.name.clone(), position))
+                })
+                .collect();
+            (set, matched_files)
+        })
+        .collect();
+
+    (matched_sets, used_files)
+}
+
+/**
+ * Write the zip files for the matched sets.
+ */
+fn write_rebuild_sets(
+    output_path: &Path,
+    sets: &[&(&RebuildSet, Vec<(String, usize)>)],
+    source_files: &[HashedFile],
+) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(output_path)?;
+
+    let progress_bar = ProgressBar::new(sets.len() as u64);
+    progress_bar.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:20.cyan/blue}] {pos}/{len} ({eta}) {msg}")
+            .progress_chars("#>-"),
+    );
+
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for (set, files) in sets {
+        progress_bar.set_message(set.name.clone());
+
+        let zip_file = File::create(output_path.join(format!("{}.zip", set.name)))?;
+        let mut zip_writer = ZipWriter::new(zip_file);
+        for (file_name, position) in files {
+            let data = source_files[*position].read()?;
+            zip_writer.start_file(file_name.as_str(), options)?;
+            zip_writer.write_all(&data)?;
+        }
+        zip_writer.finish()?;
+
+        progress_bar.inc(1);
+    }
+
+    progress_bar.finish_and_clear();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rom(name: &str, crc: &str, merge: Option<&str>) -> Rom {
+        Rom {
+            name: name.to_string(),
+            size: 1024,
+            merge: merge.map(str::to_string),
+            status: None,
+            crc: Some(crc.to_string()),
+            sha1: None,
+        }
+    }
+
+    fn machine(
+        name: &str,
+        clone_of: Option<&str>,
+        rom_of: Option<&str>,
+        roms: Vec<Rom>,
+    ) -> Machine {
+        let mut machine = Machine::new(name.to_string());
+        machine.clone_of = clone_of.map(str::to_string);
+        machine.rom_of = rom_of.map(str::to_string);
+        machine.roms = roms;
+        machine
+    }
+
+    /**
+     * BIOS, parent and clone, with the clone taking its BIOS file straight from the BIOS.
+     */
+    fn get_machines(names: &[&str]) -> HashMap<String, Machine> {
+        let bios_rom = || rom("sp-s2.sp1", "9036d879", Some("sp-s2.sp1"));
+        vec![
+            machine(
+                "neogeo",
+                None,
+                None,
+                vec![rom("sp-s2.sp1", "9036d879", None)],
+            ),
+            machine(
+                "mslug",
+                None,
+                Some("neogeo"),
+                vec![
+                    rom("201-p1.p1", "aaaaaaaa", None),
+                    rom("201-c1.c1", "cccccccc", None),
+                    bios_rom(),
+                ],
+            ),
+            machine(
+                "mslugb",
+                Some("mslug"),
+                Some("neogeo"),
+                vec![
+                    rom("b-p1.p1", "bbbbbbbb", None),
+                    rom("201-c1.c1", "cccccccc", Some("201-c1.c1")),
+                    bios_rom(),
+                ],
+            ),
+        ]
+        .into_iter()
+        .filter(|machine| names.contains(&machine.name.as_str()))
+        .map(|machine| (machine.name.clone(), machine))
+        .collect()
+    }
+
+    fn get_file_names<'a>(sets: &'a [RebuildSet], name: &str) -> Vec<&'a str> {
+        let set = sets.iter().find(|set| set.name == name).unwrap();
+        let mut names: Vec<&str> = set.files.iter().map(|file| file.name.as_str()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn non_merged_sets_have_all_their_files() {
+        let machines = get_machines(&["neogeo", "mslug", "mslugb"]);
+        let sets = get_rebuild_sets(&machines, RebuildMode::NonMerged);
+
+        assert_eq!(sets.len(), 3);
+        assert_eq!(
+            get_file_names(&sets, "mslugb"),
+            ["201-c1.c1", "b-p1.p1", "sp-s2.sp1"]
+        );
+    }
+
+    #[test]
+    fn split_sets_leave_merged_files_to_their_sets() {
+        let machines = get_machines(&["neogeo", "mslug", "mslugb"]);
+        let sets = get_rebuild_sets(&machines, RebuildMode::Split);
+
+        assert_eq!(get_file_names(&sets, "neogeo"), ["sp-s2.sp1"]);
+        assert_eq!(get_file_names(&sets, "mslug"), ["201-c1.c1", "201-p1.p1"]);
+        assert_eq!(get_file_names(&sets, "mslugb"), ["b-p1.p1"]);
+    }
+
+    #[test]
+    fn split_sets_keep_files_of_a_missing_parent() {
+        let machines = get_machines(&["neogeo", "mslugb"]);
+        let sets = get_rebuild_sets(&machines, RebuildMode::Split);
+
+        assert_eq!(get_file_names(&sets, "mslugb"), ["201-c1.c1", "b-p1.p1"]);
+    }
+
+    #[test]
+    fn merged_sets_group_clones_with_their_parent() {
+        let machines = get_machines(&["neogeo", "mslug", "mslugb"]);
+        let sets = get_rebuild_sets(&machines, RebuildMode::Merged);
+
+        assert_eq!(sets.len(), 2);
+        assert_eq!(
+            get_file_names(&sets, "mslug"),
+            ["201-c1.c1", "201-p1.p1", "b-p1.p1"]
+        );
+    }
+
+    #[test]
+    fn merged_sets_of_a_missing_parent_leave_bios_files_to_the_bios() {
+        let machines = get_machines(&["neogeo", "mslugb"]);
+        let sets = get_rebuild_sets(&machines, RebuildMode::Merged);
+
+        assert_eq!(get_file_names(&sets, "mslug"), ["201-c1.c1", "b-p1.p1"]);
+        assert_eq!(get_file_names(&sets, "neogeo"), ["sp-s2.sp1"]);
+    }
+
+    #[test]
+    fn merged_sets_of_a_missing_parent_and_bios_keep_all_files() {
+        let machines = get_machines(&["mslugb"]);
+        let sets = get_rebuild_sets(&machines, RebuildMode::Merged);
+
+        assert_eq!(
+            get_file_names(&sets, "mslug"),
+            ["201-c1.c1", "b-p1.p1", "sp-s2.sp1"]
+        );
+    }
+}
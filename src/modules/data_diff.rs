@@ -0,0 +1,615 @@
+use crate::helpers::diff_helper::{
+    diff_data_sets, find_renamed_machines, DataSetDiff, MachineChange, RenameMatch,
+    MIN_RENAME_CONFIDENCE,
+};
+use crate::helpers::escape_helper::escape_markdown;
+use crate::helpers::file_helper::{get_safe_file_name, validate_file_name};
+use crate::helpers::ui_helper::{icons::*, print_message, println_message, show_section};
+use crate::modules::data_import::read_workspace;
+use crate::MACHINES;
+use dialoguer::{console::style, theme::ColorfulTheme, Input, Select};
+use mame_parser::models::Machine;
+use num_format::{Locale, ToFormattedString};
+use prettytable::{row, Cell, Row, Table};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/**
+ * Maximum number of renamed machines shown in the table.
+ */
+const MAX_RENAMES_SHOWN: usize = 20;
+
+/**
+ * Save the current machines as a snapshot to compare with later versions.
+ */
+pub fn save_snapshot() -> Result<(), Box<dyn Error>> {
+    show_section("Save snapshot");
+
+    let machines = MACHINES.lock().unwrap();
+
+    if machines.is_empty() {
+        let message = format!(
+            "Error: {}",
+            "No machines data loaded, please read the data first."
+        );
+        println_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let default_name = format!("snapshot_{}", chrono::Local::now().format("%Y%m%d"));
+    let name: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Snapshot name")
+        .default(default_name)
+        .validate_with(|name: &String| validate_file_name(name))
+        .interact_text()?;
+
+    let time = std::time::Instant::now();
+
+    let snapshot_path = get_snapshots_path().join(format!("{}.json", get_safe_file_name(&name)));
+    let message = format!("Writing {}", style(snapshot_path.display()).cyan());
+    println_message(&message, WRITE);
+
+    match write_snapshot(&snapshot_path, &machines) {
+        Ok(()) => {
+            let rounded_secs = (time.elapsed().as_secs_f32() * 10.0).round() / 10.0;
+            let message = format!(
+                "Snapshot of {} machines saved in {}s",
+                machines.len().to_formatted_string(&Locale::en),
+                rounded_secs
+            );
+            print_message(&message, SUCCESS);
+        }
+        Err(err) => {
+            let message = format!("Error: {}", err);
+            print_message(&message, ERROR);
+        }
+    }
+
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Compare two data sets and show what changed between them, with an optional changelog export.
+ */
+pub fn compare_data_sets() -> Result<(), Box<dyn Error>> {
+    show_section("Compare data sets");
+
+    let old_machines = match select_data_set("Old data set")? {
+        Some(machines) => machines,
+        None => {
+            println!();
+            return Ok(());
+        }
+    };
+    let new_machines = match select_data_set("New data set")? {
+        Some(machines) => machines,
+        None => {
+            println!();
+            return Ok(());
+        }
+    };
+
+    let time = std::time::Instant::now();
+
+    let diff = diff_data_sets(&old_machines, &new_machines);
+
+    let rounded_secs = (time.elapsed().as_secs_f32() * 10.0).round() / 10.0;
+    let message = format!(
+        "{} changes found in {}s",
+        diff.all_changes().len().to_formatted_string(&Locale::en),
+        rounded_secs
+    );
+    println_message(&message, SUCCESS);
+
+    show_diff_summary(&old_machines, &new_machines, &diff);
+
+    let formats = &["CSV", "JSON", "Markdown", "Skip"];
+    let format = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Export changelog")
+        .default(0)
+        .items(&formats[..])
+        .interact()
+        .unwrap();
+
+    let extension = match format {
+        0 => "csv",
+        1 => "json",
+        2 => "md",
+        3 => {
+            println!();
+            return Ok(());
+        }
+        _ => unreachable!(),
+    };
+
+    let report_path = Path::new("data")
+        .join("reports")
+        .join(format!("changelog.{}", extension));
+
+    match write_changelog(&report_path, &diff) {
+        Ok(()) => {
+            let message = format!(
+                "Changelog written to {}",
+                style(report_path.display()).cyan()
+            );
+            print_message(&message, SUCCESS);
+        }
+        Err(err) => {
+            let message = format!("Error: {}", err);
+            print_message(&message, ERROR);
+        }
+    }
+
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Find the machines renamed between two data sets and export the old to new name mapping.
+ */
+pub fn detect_renamed_machines() -> Result<(), Box<dyn Error>> {
+    show_section("Detect renamed machines");
+
+    let old_machines = match select_data_set("Old data set")? {
+        Some(machines) => machines,
+        None => {
+            println!();
+            return Ok(());
+        }
+    };
+    let new_machines = match select_data_set("New data set")? {
+        Some(machines) => machines,
+        None => {
+            println!();
+            return Ok(());
+        }
+    };
+
+    let min_confidence: f64 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Minimum confidence (% of ROMs in common)")
+        .default(MIN_RENAME_CONFIDENCE)
+        .interact_text()?;
+
+    let time = std::time::Instant::now();
+
+    let renames = find_renamed_machines(&old_machines, &new_machines, min_confidence);
+
+    let rounded_secs = (time.elapsed().as_secs_f32() * 10.0).round() / 10.0;
+    let message = format!(
+        "{} renamed machines found in {}s",
+        renames.len().to_formatted_string(&Locale::en),
+        rounded_secs
+    );
+    println_message(&message, SUCCESS);
+
+    if renames.is_empty() {
+        println!();
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.set_titles(Row::new(vec![
+        Cell::new("Renamed machines").style_spec("H4cFg")
+    ]));
+
+    table.add_row(row![b -> "Old name", "New name", "Description", "Confidence"]);
+    for rename in renames.iter().take(MAX_RENAMES_SHOWN) {
+        let description = new_machines
+            .get(&rename.new_name)
+            .and_then(|machine| machine.description.clone())
+            .unwrap_or_default();
+        table.add_row(row![
+            rename.old_name,
+            rename.new_name,
+            description,
+            r -> format!("{:.1}%", rename.confidence)
+        ]);
+    }
+
+    table.printstd();
+
+    if renames.len() > MAX_RENAMES_SHOWN {
+        let message = format!(
+            "Showing {} of {} renamed machines",
+            MAX_RENAMES_SHOWN,
+            renames.len().to_formatted_string(&Locale::en)
+        );
+        println_message(&message, WRITE);
+    }
+
+    let mapping_path = get_rename_mapping_path();
+    match write_rename_mapping(&mapping_path, &renames) {
+        Ok(()) => {
+            let message = format!(
+                "Rename mapping written to {}",
+                style(mapping_path.display()).cyan()
+            );
+            print_message(&message, SUCCESS);
+        }
+        Err(err) => {
+            let message = format!("Error: {}", err);
+            print_message(&message, ERROR);
+        }
+    }
+
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Replace the old machine names of a list with the new ones from a rename mapping file.
+ * Text lists have a machine name per line, CSV lists have it in the first column.
+ */
+pub fn migrate_machine_list() -> Result<(), Box<dyn Error>> {
+    show_section("Migrate a machine list");
+
+    let theme = ColorfulTheme::default();
+
+    let list: String = Input::with_theme(&theme)
+        .with_prompt("Machine list file")
+        .interact_text()?;
+    let mapping: String = Input::with_theme(&theme)
+        .with_prompt("Rename mapping file")
+        .default(get_rename_mapping_path().display().to_string())
+        .interact_text()?;
+
+    let list_path = PathBuf::from(list);
+    let renames = match read_rename_mapping(Path::new(&mapping)) {
+        Ok(renames) => renames,
+        Err(err) => {
+            let message = format!("Error: {}", err);
+            println_message(&message, ERROR);
+            println!();
+            return Ok(());
+        }
+    };
+
+    let file_stem = list_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let migrated_path = match list_path.extension() {
+        Some(extension) => list_path.with_file_name(format!(
+            "{}_migrated.{}",
+            file_stem,
+            extension.to_string_lossy()
+        )),
+        None => list_path.with_file_name(format!("{}_migrated", file_stem)),
+    };
+
+    match migrate_list(&list_path, &migrated_path, &renames) {
+        Ok(count) => {
+            let message = format!(
+                "{} machines renamed, list written to {}",
+                count.to_formatted_string(&Locale::en),
+                style(migrated_path.display()).cyan()
+            );
+            print_message(&message, SUCCESS);
+        }
+        Err(err) => {
+            let message = format!("Error: {}", err);
+            print_message(&message, ERROR);
+        }
+    }
+
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Ask where to load a data set from: the current data, a saved snapshot or another workspace.
+ */
+fn select_data_set(prompt: &str) -> Result<Option<HashMap<String, Machine>>, Box<dyn Error>> {
+    let sources = &[
+        "Current data",
+        "Saved snapshot",
+        "Workspace folder",
+        "< Back",
+    ];
+    let source = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .default(0)
+        .items(&sources[..])
+        .interact()
+        .unwrap();
+
+    let machines = match source {
+        0 => MACHINES.lock().unwrap().clone(),
+        1 => {
+            let snapshots = get_snapshots()?;
+            if snapshots.is_empty() {
+                let message = format!("Error: {}", "No snapshots saved yet.");
+                println_message(&message, ERROR);
+                return Ok(None);
+            }
+
+            let names: Vec<String> = snapshots
+                .iter()
+                .map(|path| path.file_stem().unwrap().to_string_lossy().to_string())
+                .collect();
+            let selection = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt("Snapshot")
+                .default(0)
+                .items(&names[..])
+                .interact()
+                .unwrap();
+
+            let file = File::open(&snapshots[selection])?;
+            serde_json::from_reader(std::io::BufReader::new(file))?
+        }
+        2 => {
+            let folder: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("Workspace folder")
+                .default("data".to_string())
+                .interact_text()?;
+            match read_workspace(Path::new(&folder)) {
+                Ok(machines) => machines,
+                Err(err) => {
+                    let message = format!("Error: {}", err);
+                    println_message(&message, ERROR);
+                    return Ok(None);
+                }
+            }
+        }
+        3 => return Ok(None),
+        _ => unreachable!(),
+    };
+
+    if machines.is_empty() {
+        let message = format!("Error: {}", "The data set has no machines.");
+        println_message(&message, ERROR);
+        return Ok(None);
+    }
+
+    Ok(Some(machines))
+}
+
+/**
+ * Show how many machines were added, removed, renamed or changed.
+ */
+fn show_diff_summary(
+    old_machines: &HashMap<String, Machine>,
+    new_machines: &HashMap<String, Machine>,
+    diff: &DataSetDiff,
+) {
+    let mut table = Table::new();
+    table.set_titles(Row::new(vec![
+        Cell::new("Data set changes").style_spec("H2cFg")
+    ]));
+
+    table.add_row(row![b -> "Change", "Machines"]);
+    let rows = [
+        ("Machines in old data set", old_machines.len()),
+        ("Machines in new data set", new_machines.len()),
+        ("Added", diff.added.len()),
+        ("Removed", diff.removed.len()),
+        ("Renamed", diff.renamed.len()),
+        ("Driver status changed", diff.count_changed("driver_status")),
+        ("Description changed", diff.count_changed("description")),
+        ("Year changed", diff.count_changed("year")),
+        ("Category changed", diff.count_changed("category")),
+        ("ROMs changed", diff.count_roms_changed()),
+    ];
+    for (change, count) in rows {
+        table.add_row(row![change, r -> count.to_formatted_string(&Locale::en)]);
+    }
+
+    table.printstd();
+}
+
+/**
+ * Write the changelog as CSV, JSON or Markdown depending on the file extension.
+ */
+fn write_changelog(report_path: &Path, diff: &DataSetDiff) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = report_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let changes = diff.all_changes();
+
+    match report_path
+        .extension()
+        .and_then(|extension| extension.to_str())
+    {
+        Some("csv") => {
+            let mut writer = csv::Writer::from_path(report_path)?;
+            for change in changes {
+                writer.serialize(change)?;
+            }
+            writer.flush()?;
+        }
+        Some("json") => {
+            let file = File::create(report_path)?;
+            serde_json::to_writer_pretty(file, &changes)?;
+        }
+        _ => write_markdown_changelog(report_path, diff)?,
+    }
+
+    Ok(())
+}
+
+/**
+ * Write the changelog as a Markdown document with a section for each kind of change.
+ */
+fn write_markdown_changelog(report_path: &Path, diff: &DataSetDiff) -> Result<(), Box<dyn Error>> {
+    let mut file = File::create(report_path)?;
+
+    writeln!(file, "# Changelog")?;
+
+    let sections: [(&str, &[MachineChange]); 5] = [
+        ("Added", &diff.added),
+        ("Removed", &diff.removed),
+        ("Renamed", &diff.renamed),
+        ("Changed", &diff.changed),
+        ("ROMs", &diff.roms),
+    ];
+    for (title, changes) in sections {
+        writeln!(file)?;
+        writeln!(file, "## {} ({})", title, changes.len())?;
+        writeln!(file)?;
+        if changes.is_empty() {
+            writeln!(file, "None.")?;
+            continue;
+        }
+        writeln!(file, "| Machine | Field | Old value | New value |")?;
+        writeln!(file, "|---|---|---|---|")?;
+        for change in changes {
+            writeln!(
+                file,
+                "| {} | {} | {} | {} |",
+                change.machine,
+                change.field,
+                escape_markdown(&change.old_value),
+                escape_markdown(&change.new_value)
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/**
+ * Write the old name to new name mapping as CSV.
+ */
+fn write_rename_mapping(
+    mapping_path: &Path,
+    renames: &[RenameMatch],
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = mapping_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut writer = csv::Writer::from_path(mapping_path)?;
+    for rename in renames {
+        writer.serialize(rename)?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/**
+ * Read the old name to new name mapping written by the rename detection.
+ */
+fn read_rename_mapping(mapping_path: &Path) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    let mut reader = csv::Reader::from_path(mapping_path)?;
+
+    let mut renames = HashMap::new();
+    for record in reader.records() {
+        let record = record?;
+        if let (Some(old_name), Some(new_name)) = (record.get(0), record.get(1)) {
+            renames.insert(old_name.to_string(), new_name.to_string());
+        }
+    }
+
+    Ok(renames)
+}
+
+/**
+ * Write a copy of the list with the renamed machines, returning how many were renamed.
+ */
+fn migrate_list(
+    list_path: &Path,
+    migrated_path: &Path,
+    renames: &HashMap<String, String>,
+) -> Result<usize, Box<dyn Error>> {
+    let mut count = 0;
+
+    let is_csv = list_path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+
+    if is_csv {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_path(list_path)?;
+        let mut writer = csv::WriterBuilder::new()
+            .flexible(true)
+            .from_path(migrated_path)?;
+        for record in reader.records() {
+            let mut fields: Vec<String> = record?.iter().map(|field| field.to_string()).collect();
+            if let Some(new_name) = fields.first().and_then(|name| renames.get(name.trim())) {
+                fields[0] = new_name.clone();
+                count += 1;
+            }
+            writer.write_record(&fields)?;
+        }
+        writer.flush()?;
+    } else {
+        let content = fs::read_to_string(list_path)?;
+        let mut file = File::create(migrated_path)?;
+        for line in content.lines() {
+            match renames.get(line.trim()) {
+                Some(new_name) => {
+                    writeln!(file, "{}", new_name)?;
+                    count += 1;
+                }
+                None => writeln!(file, "{}", line)?,
+            }
+        }
+    }
+
+    Ok(count)
+}
+
+/**
+ * Write the machines to a snapshot file.
+ */
+fn write_snapshot(
+    snapshot_path: &Path,
+    machines: &HashMap<String, Machine>,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = snapshot_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let file = File::create(snapshot_path)?;
+    serde_json::to_writer(std::io::BufWriter::new(file), machines)?;
+
+    Ok(())
+}
+
+/**
+ * Get the saved snapshot files sorted by name.
+ */
+fn get_snapshots() -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let snapshots_path = get_snapshots_path();
+    if !snapshots_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut snapshots: Vec<PathBuf> = fs::read_dir(snapshots_path)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect();
+    snapshots.sort();
+
+    Ok(snapshots)
+}
+
+/**
+ * Get the path of the file with the old name to new name mapping of the renamed machines.
+ */
+fn get_rename_mapping_path() -> PathBuf {
+    Path::new("data").join("reports").join("rename_mapping.csv")
+}
+
+/**
+ * Get the path of the folder where the snapshots are saved.
+ */
+fn get_snapshots_path() -> PathBuf {
+    Path::new("data").join("snapshots")
+}
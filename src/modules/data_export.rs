@@ -1,10 +1,18 @@
+use crate::helpers::dependency_helper::get_missing_dependencies;
+use crate::helpers::details_writer::{
+    remove_required_machines, write_machine_details, write_required_machines,
+};
+use crate::helpers::graph_helper::{build_dependency_graph, write_dot, write_graphml};
 use crate::helpers::ui_helper::{icons::*, print_message, println_message, show_section};
-use crate::MACHINES;
-use dialoguer::{console::style, theme::ColorfulTheme, Select};
+use crate::modules::data_collections::export_required_files;
+use crate::{FILTER_LOG, FLAG_REQUIRED_MACHINES, MACHINES, MACHINE_DETAILS};
+use dialoguer::{console::style, theme::ColorfulTheme, Input, Select};
 use indicatif::{ProgressBar, ProgressStyle};
 use mame_parser::file_handling::write_files;
-use mame_parser::models::ExportFileType;
+use mame_parser::models::{ExportFileType, Machine};
 use mame_parser::progress::{CallbackType, ProgressCallback, ProgressInfo};
+use num_format::{Locale, ToFormattedString};
+use std::collections::HashMap;
 use std::error::Error;
 use std::path::Path;
 
@@ -17,6 +25,8 @@ pub fn show_export_submenu() -> Result<(), Box<dyn Error>> {
             "Export to SQLite",
             "Export to JSON",
             "Export to CSV",
+            "Export required files list",
+            "Export dependency graph",
             "Back",
         ];
         let selection = Select::with_theme(&ColorfulTheme::default())
@@ -29,7 +39,9 @@ pub fn show_export_submenu() -> Result<(), Box<dyn Error>> {
             0 => export_sqlite()?,
             1 => export_json()?,
             2 => export_csv()?,
-            3 => {
+            3 => export_required_files()?,
+            4 => export_dependency_graph()?,
+            5 => {
                 break;
             }
             _ => unreachable!(),
@@ -96,6 +108,16 @@ fn export_sqlite() -> Result<(), Box<dyn Error>> {
         return Ok(());
     }
 
+    let export_folder = result.unwrap();
+    let result = write_additional_data(ExportFileType::Sqlite, &export_folder, &machines);
+
+    if result.is_err() {
+        let message = format!("Error: {}", result.err().unwrap());
+        print_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
     let rounded_secs = (time.elapsed().as_secs_f32() * 10.0).round() / 10.0;
     let message = format!("Database created in {}s", rounded_secs);
     print_message(&message, SUCCESS);
@@ -145,7 +167,7 @@ fn export_json() -> Result<(), Box<dyn Error>> {
 
     let time = std::time::Instant::now();
 
-    let message = format!("Creating JSON files");
+    let message = "Creating JSON files".to_string();
     println_message(&message, WRITE);
 
     let result = write_files(
@@ -162,6 +184,16 @@ fn export_json() -> Result<(), Box<dyn Error>> {
         return Ok(());
     }
 
+    let export_folder = result.unwrap();
+    let result = write_additional_data(ExportFileType::Json, &export_folder, &machines);
+
+    if result.is_err() {
+        let message = format!("Error: {}", result.err().unwrap());
+        print_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
     let rounded_secs = (time.elapsed().as_secs_f32() * 10.0).round() / 10.0;
     let message = format!("JSON files created in {}s", rounded_secs);
     print_message(&message, SUCCESS);
@@ -211,7 +243,7 @@ fn export_csv() -> Result<(), Box<dyn Error>> {
 
     let time = std::time::Instant::now();
 
-    let message = format!("Creating CSV files");
+    let message = "Creating CSV files".to_string();
     println_message(&message, WRITE);
 
     let result = write_files(
@@ -228,6 +260,16 @@ fn export_csv() -> Result<(), Box<dyn Error>> {
         return Ok(());
     }
 
+    let export_folder = result.unwrap();
+    let result = write_additional_data(ExportFileType::Csv, &export_folder, &machines);
+
+    if result.is_err() {
+        let message = format!("Error: {}", result.err().unwrap());
+        print_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
     let rounded_secs = (time.elapsed().as_secs_f32() * 10.0).round() / 10.0;
     let message = format!("CSV files created in {}s", rounded_secs);
     print_message(&message, SUCCESS);
@@ -236,3 +278,128 @@ fn export_csv() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+/**
+ * Export the parent, BIOS and device dependencies of all the machines, or of a single one,
+ * as a Graphviz DOT or GraphML graph.
+ */
+fn export_dependency_graph() -> Result<(), Box<dyn Error>> {
+    show_section("Export dependency graph");
+
+    let machines = MACHINES.lock().unwrap();
+
+    if machines.is_empty() {
+        let message = format!(
+            "Error: {}",
+            "No machines data loaded, please read the data first."
+        );
+        print_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let theme = ColorfulTheme::default();
+
+    let scopes = &["All machines", "Single machine"];
+    let scope = Select::with_theme(&theme)
+        .with_prompt("Machines")
+        .default(0)
+        .items(&scopes[..])
+        .interact()
+        .unwrap();
+
+    let root = if scope == 1 {
+        let name: String = Input::with_theme(&theme)
+            .with_prompt("Machine name")
+            .interact_text()?;
+        let name = name.trim().to_string();
+        if !machines.contains_key(&name) {
+            let message = format!("Error: Machine {} not found.", name);
+            print_message(&message, ERROR);
+            println!();
+            return Ok(());
+        }
+        Some(name)
+    } else {
+        None
+    };
+
+    let formats = &["Graphviz DOT", "GraphML"];
+    let format = Select::with_theme(&theme)
+        .with_prompt("Format")
+        .default(0)
+        .items(&formats[..])
+        .interact()
+        .unwrap();
+
+    let time = std::time::Instant::now();
+
+    let graph = build_dependency_graph(&machines, root.as_deref());
+
+    let file_name = format!(
+        "{}.{}",
+        root.as_ref()
+            .map(|name| format!("{}_dependencies", name))
+            .unwrap_or_else(|| "dependencies".to_string()),
+        if format == 0 { "dot" } else { "graphml" }
+    );
+    let file_path = Path::new("data")
+        .join("export")
+        .join("graph")
+        .join(file_name);
+
+    let message = format!("Creating {}", style(file_path.display()).cyan());
+    println_message(&message, WRITE);
+
+    let result = if format == 0 {
+        write_dot(&file_path, &graph)
+    } else {
+        write_graphml(&file_path, &graph)
+    };
+
+    if result.is_err() {
+        let message = format!("Error: {}", result.err().unwrap());
+        print_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let rounded_secs = (time.elapsed().as_secs_f32() * 10.0).round() / 10.0;
+    let message = format!(
+        "Graph with {} nodes and {} edges created in {}s",
+        graph.nodes.len().to_formatted_string(&Locale::en),
+        graph.edges.len().to_formatted_string(&Locale::en),
+        rounded_secs
+    );
+    print_message(&message, SUCCESS);
+
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Write the data not exported by mame-parser: machine details, player counts and, when they
+ * were flagged in the resolve dependencies step, the machines required by the exported set
+ * that are not part of it. Otherwise the required machines of a previous export are removed.
+ */
+fn write_additional_data(
+    export_file_type: ExportFileType,
+    export_folder: &Path,
+    machines: &HashMap<String, Machine>,
+) -> Result<(), Box<dyn Error>> {
+    let machines_details = MACHINE_DETAILS.lock().unwrap();
+    write_machine_details(export_file_type, export_folder, machines, &machines_details)?;
+
+    if !*FLAG_REQUIRED_MACHINES.lock().unwrap() {
+        return remove_required_machines(export_file_type, export_folder);
+    }
+
+    let filter_log = FILTER_LOG.lock().unwrap();
+    let removed_machines = filter_log
+        .iter()
+        .map(|entry| (entry.machine.name.as_str(), &entry.machine))
+        .collect();
+    let missing_dependencies = get_missing_dependencies(machines, &removed_machines);
+    write_required_machines(export_file_type, export_folder, &missing_dependencies)
+}
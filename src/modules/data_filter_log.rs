@@ -0,0 +1,350 @@
+use crate::helpers::dependency_helper::{get_missing_dependencies, MissingDependency};
+use crate::helpers::ui_helper::{icons::*, print_message, println_message, show_section};
+use crate::{FILTER_LOG, FLAG_REQUIRED_MACHINES, MACHINES};
+use chrono::{DateTime, Local};
+use dialoguer::{console::style, theme::ColorfulTheme, Input, Select};
+use mame_parser::models::Machine;
+use prettytable::{row, Cell, Row, Table};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs::{self, File};
+use std::path::Path;
+
+/**
+ * Maximum number of entries shown when looking up removed machines by description.
+ */
+const MAX_LOOKUP_RESULTS: usize = 20;
+
+/**
+ * Machine removed by a filter, with the rule that removed it and when.
+ */
+pub struct FilterLogEntry {
+    pub machine: Machine,
+    pub rule: String,
+    pub removed_at: DateTime<Local>,
+}
+
+/**
+ * Filter log entry as written to the exported files.
+ */
+#[derive(Serialize)]
+struct FilterLogRecord<'a> {
+    name: &'a str,
+    description: &'a str,
+    rule: &'a str,
+    removed_at: String,
+}
+
+impl<'a> From<&'a FilterLogEntry> for FilterLogRecord<'a> {
+    fn from(entry: &'a FilterLogEntry) -> Self {
+        FilterLogRecord {
+            name: &entry.machine.name,
+            description: entry.machine.description.as_deref().unwrap_or_default(),
+            rule: &entry.rule,
+            removed_at: format_time(&entry.removed_at),
+        }
+    }
+}
+
+/**
+ * Record the machines removed by a filter rule.
+ */
+pub fn record_removed_machines(removed_machines: Vec<Machine>, rule: &str) {
+    let removed_at = Local::now();
+    let mut filter_log = FILTER_LOG.lock().unwrap();
+
+    for machine in removed_machines {
+        filter_log.push(FilterLogEntry {
+            machine,
+            rule: rule.to_string(),
+            removed_at,
+        });
+    }
+}
+
+/**
+ * Show why a machine is not in the current data set.
+ */
+pub fn show_why_missing() -> Result<(), Box<dyn Error>> {
+    show_section("Why is a machine missing?");
+
+    let query: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Machine name or description")
+        .interact_text()?;
+    let query = query.trim().to_lowercase();
+
+    let machines = MACHINES.lock().unwrap();
+    let filter_log = FILTER_LOG.lock().unwrap();
+
+    if machines.contains_key(&query) {
+        let message = format!("{} is in the current data set", style(&query).cyan());
+        println_message(&message, SUCCESS);
+        println!();
+        return Ok(());
+    }
+
+    let mut entries: Vec<&FilterLogEntry> = filter_log
+        .iter()
+        .filter(|entry| entry.machine.name.to_lowercase() == query)
+        .collect();
+
+    if entries.is_empty() {
+        entries = filter_log
+            .iter()
+            .filter(|entry| {
+                entry
+                    .machine
+                    .description
+                    .as_ref()
+                    .is_some_and(|description| description.to_lowercase().contains(&query))
+            })
+            .collect();
+    }
+
+    if entries.is_empty() {
+        let message = format!(
+            "Error: {} was not removed by any filter and is not in the loaded data",
+            query
+        );
+        println_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.set_titles(Row::new(vec![
+        Cell::new("Removed machines").style_spec("H4cFg")
+    ]));
+    table.add_row(row![b -> "Machine", "Description", "Rule", "Removed at"]);
+    for entry in entries.iter().take(MAX_LOOKUP_RESULTS) {
+        table.add_row(row![
+            entry.machine.name,
+            entry.machine.description.as_deref().unwrap_or_default(),
+            entry.rule,
+            format_time(&entry.removed_at)
+        ]);
+    }
+    table.printstd();
+    if entries.len() > MAX_LOOKUP_RESULTS {
+        println!(
+            "... and {} more machines",
+            entries.len() - MAX_LOOKUP_RESULTS
+        );
+    }
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Find the parents, BIOS and devices needed by the current machines that are not in the data set,
+ * and add back the ones removed by filters.
+ */
+pub fn resolve_dependencies() -> Result<(), Box<dyn Error>> {
+    show_section("Resolve dependencies");
+
+    let mut machines = MACHINES.lock().unwrap();
+
+    if machines.is_empty() {
+        let message = format!(
+            "Error: {}",
+            "No machines data loaded, please read the data first."
+        );
+        println_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let mut filter_log = FILTER_LOG.lock().unwrap();
+
+    let missing_dependencies = {
+        let removed_machines: HashMap<&str, &Machine> = filter_log
+            .iter()
+            .map(|entry| (entry.machine.name.as_str(), &entry.machine))
+            .collect();
+        get_missing_dependencies(&machines, &removed_machines)
+    };
+
+    if missing_dependencies.is_empty() {
+        let message = "All the dependencies of the current machines are in the data set";
+        println_message(message, SUCCESS);
+        println!();
+        return Ok(());
+    }
+
+    show_missing_dependencies(&missing_dependencies);
+
+    let available: HashSet<&str> = missing_dependencies
+        .iter()
+        .filter(|dependency| dependency.is_available)
+        .map(|dependency| dependency.name.as_str())
+        .collect();
+    let unavailable = missing_dependencies.len() - available.len();
+    if unavailable > 0 {
+        let message = format!(
+            "{} required machines are not in the loaded data and can't be added back",
+            unavailable
+        );
+        println_message(&message, ERROR);
+    }
+
+    if available.is_empty() {
+        println!();
+        return Ok(());
+    }
+
+    let selections = &[
+        format!("Add back the {} required machines", available.len()),
+        "Keep them hidden and flag them as required in exports".to_string(),
+    ];
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .default(0)
+        .items(&selections[..])
+        .interact()
+        .unwrap();
+
+    if selection == 1 {
+        *FLAG_REQUIRED_MACHINES.lock().unwrap() = true;
+        let message = "Required machines will be listed in the required_machines export";
+        println_message(message, SUCCESS);
+        println!();
+        return Ok(());
+    }
+
+    let (restored_entries, kept_entries): (Vec<FilterLogEntry>, Vec<FilterLogEntry>) = filter_log
+        .drain(..)
+        .partition(|entry| available.contains(entry.machine.name.as_str()));
+    *filter_log = kept_entries;
+
+    *FLAG_REQUIRED_MACHINES.lock().unwrap() = false;
+
+    let total_restored = restored_entries.len();
+    for entry in restored_entries {
+        machines.insert(entry.machine.name.clone(), entry.machine);
+    }
+
+    let message = format!("{} required machines added back", total_restored);
+    println_message(&message, SUCCESS);
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Show the machines needed by the current machines that are not in the data set.
+ */
+fn show_missing_dependencies(missing_dependencies: &[MissingDependency]) {
+    let mut table = Table::new();
+    table.set_titles(Row::new(vec![
+        Cell::new("Missing dependencies").style_spec("H4cFg")
+    ]));
+    table.add_row(row![b -> "Machine", "Type", "Required by", "Removed by a filter"]);
+    for dependency in missing_dependencies.iter().take(MAX_LOOKUP_RESULTS) {
+        let mut required_by = dependency
+            .required_by
+            .iter()
+            .take(3)
+            .cloned()
+            .collect::<Vec<String>>()
+            .join(", ");
+        if dependency.required_by.len() > 3 {
+            required_by.push_str(&format!(" and {} more", dependency.required_by.len() - 3));
+        }
+        table.add_row(row![
+            dependency.name,
+            dependency.kind,
+            required_by,
+            if dependency.is_available { "Yes" } else { "No" }
+        ]);
+    }
+    table.printstd();
+    if missing_dependencies.len() > MAX_LOOKUP_RESULTS {
+        println!(
+            "... and {} more machines",
+            missing_dependencies.len() - MAX_LOOKUP_RESULTS
+        );
+    }
+}
+
+/**
+ * Export the filter log to CSV or JSON.
+ */
+pub fn export_filter_log() -> Result<(), Box<dyn Error>> {
+    show_section("Export filter log");
+
+    let selections = &["CSV", "JSON"];
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .default(0)
+        .items(&selections[..])
+        .interact()
+        .unwrap();
+
+    let filter_log = FILTER_LOG.lock().unwrap();
+
+    if filter_log.is_empty() {
+        let message = format!("Error: {}", "No machines have been removed by filters yet.");
+        println_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let reports_path = Path::new("data").join("reports");
+    let file_path = reports_path.join(match selection {
+        0 => "filter_log.csv",
+        1 => "filter_log.json",
+        _ => unreachable!(),
+    });
+
+    let message = format!("Writing {}", style(file_path.display()).cyan());
+    println_message(&message, WRITE);
+
+    let records: Vec<FilterLogRecord> = filter_log.iter().map(FilterLogRecord::from).collect();
+
+    let result = write_filter_log(&file_path, &records, selection == 1);
+
+    if let Err(err) = result {
+        let message = format!("Error: {}", err);
+        print_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let message = format!("{} removed machines exported", records.len());
+    print_message(&message, SUCCESS);
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Write the filter log records to a CSV or JSON file.
+ */
+fn write_filter_log(
+    file_path: &Path,
+    records: &[FilterLogRecord],
+    as_json: bool,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    if as_json {
+        serde_json::to_writer_pretty(File::create(file_path)?, records)?;
+    } else {
+        let mut writer = csv::Writer::from_path(file_path)?;
+        for record in records {
+            writer.serialize(record)?;
+        }
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+/**
+ * Format the time a machine was removed.
+ */
+fn format_time(time: &DateTime<Local>) -> String {
+    time.format("%Y-%m-%d %H:%M:%S").to_string()
+}
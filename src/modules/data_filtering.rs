@@ -1,10 +1,51 @@
+use crate::helpers::details_helper::{
+    get_control_types, get_control_types_list, get_feature_issues, get_feature_issues_list,
+    get_orientation,
+};
+use crate::helpers::machine_helper::{get_total_rom_size, requires_disks, requires_samples};
+use crate::helpers::players_helper::get_player_count;
 use crate::helpers::ui_helper::{icons::*, print_message, println_message, show_section};
-use crate::MACHINES;
-use dialoguer::{theme::ColorfulTheme, Select};
+use crate::modules::data_cabinets::filter_by_cabinet_profile;
+use crate::modules::data_filter_log::{
+    export_filter_log, record_removed_machines, resolve_dependencies, show_why_missing,
+};
+use crate::{MACHINES, MACHINE_DETAILS};
+use dialoguer::{theme::ColorfulTheme, Confirm, Input, MultiSelect, Select};
 use mame_parser::file_handling::{remove_machines_by_category, remove_machines_by_filter};
-use mame_parser::models::{Category, MachineFilter};
+use mame_parser::models::{Category, Machine, MachineFilter};
+use num_format::{Locale, ToFormattedString};
+use regex::RegexBuilder;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 
+/**
+ * Resource types available in resources.dat.
+ */
+pub const RESOURCE_TYPES: &[&str] = &[
+    "artpreview",
+    "bosses",
+    "cabinets",
+    "covers",
+    "cpanel",
+    "devices",
+    "ends",
+    "flyers",
+    "gameover",
+    "howto",
+    "icons",
+    "logo",
+    "manuals",
+    "marquees",
+    "pcb",
+    "scores",
+    "select",
+    "snap",
+    "titles",
+    "versus",
+    "videosnaps",
+    "warning",
+];
+
 /**
  * Show the filter submenu.
  */
@@ -17,6 +58,21 @@ pub fn show_filtering_submenu() -> Result<(), Box<dyn Error>> {
             "Remove mechanical machines",
             "Remove modified machines",
             "Remove clones",
+            "Keep machines with resources",
+            "Remove machines requiring disks",
+            "Remove machines requiring samples",
+            "Remove machines exceeding a ROM size",
+            "Keep machines with control types",
+            "Filter by screen orientation",
+            "Remove machines exceeding a resolution",
+            "Filter by refresh rate",
+            "Keep machines fitting a cabinet profile",
+            "Filter by number of players",
+            "Remove machines with emulation issues",
+            "Filter by text pattern",
+            "Resolve dependencies",
+            "Why is a machine missing?",
+            "Export filter log",
             "< Back",
         ];
         let selection = Select::with_theme(&ColorfulTheme::default())
@@ -32,7 +88,34 @@ pub fn show_filtering_submenu() -> Result<(), Box<dyn Error>> {
             3 => remove_non_games(MachineFilter::Mechanical)?,
             4 => remove_non_games(MachineFilter::Modified)?,
             5 => remove_non_games(MachineFilter::Clones)?,
-            6 => {
+            6 => keep_machines_with_resources()?,
+            7 => {
+                let rule = "Remove machines requiring disks";
+                show_section(rule);
+                keep_machines(rule, "Removing machines requiring disks", |machine| {
+                    !requires_disks(machine)
+                })?
+            }
+            8 => {
+                let rule = "Remove machines requiring samples";
+                show_section(rule);
+                keep_machines(rule, "Removing machines requiring samples", |machine| {
+                    !requires_samples(machine)
+                })?
+            }
+            9 => remove_machines_exceeding_rom_size()?,
+            10 => keep_machines_with_control_types()?,
+            11 => filter_by_orientation()?,
+            12 => remove_machines_exceeding_resolution()?,
+            13 => filter_by_refresh_rate()?,
+            14 => filter_by_cabinet_profile()?,
+            15 => filter_by_players()?,
+            16 => remove_machines_with_feature_issues()?,
+            17 => filter_by_text_pattern()?,
+            18 => resolve_dependencies()?,
+            19 => show_why_missing()?,
+            20 => export_filter_log()?,
+            21 => {
                 break;
             }
             _ => unreachable!(),
@@ -48,7 +131,7 @@ pub fn show_filtering_submenu() -> Result<(), Box<dyn Error>> {
 fn remove_non_game_categories() -> Result<(), Box<dyn Error>> {
     show_section("Remove machines by non game categories");
 
-    let message = format!("Removing machines by non game categories");
+    let message = "Removing machines by non game categories".to_string();
     println_message(&message, WRITE);
 
     let time = std::time::Instant::now();
@@ -102,10 +185,13 @@ fn remove_non_game_categories() -> Result<(), Box<dyn Error>> {
             return Ok(());
         }
 
-        let removed_machines = machines_guard.len() - filtered_machines.as_ref().unwrap().len();
+        let removed_machines = replace_machines(
+            &mut machines_guard,
+            filtered_machines?,
+            "Remove machines by non game categories",
+        );
         let rounded_secs = (time.elapsed().as_secs_f32() * 10.0).round() / 10.0;
         let message = format!("{} machines removed in {}s", removed_machines, rounded_secs);
-        *machines_guard = filtered_machines?;
         print_message(&message, SUCCESS);
         println!();
     }
@@ -145,13 +231,580 @@ fn remove_non_games(remove_filter: MachineFilter) -> Result<(), Box<dyn Error>>
             return Ok(());
         }
 
-        let removed_machines = machines_guard.len() - filtered_machines.as_ref().unwrap().len();
+        let removed_machines =
+            replace_machines(&mut machines_guard, filtered_machines?, &section_name);
         let rounded_secs = (time.elapsed().as_secs_f32() * 10.0).round() / 10.0;
         let message = format!("{} machines removed in {}s", removed_machines, rounded_secs);
-        *machines_guard = filtered_machines?;
         print_message(&message, SUCCESS);
         println!();
     }
 
     Ok(())
 }
+
+/**
+ * Keep only the machines having all the selected resource types.
+ */
+fn keep_machines_with_resources() -> Result<(), Box<dyn Error>> {
+    show_section("Keep machines with resources");
+
+    let selected = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Required resource types (space to select, enter to confirm)")
+        .items(RESOURCE_TYPES)
+        .interact()
+        .unwrap();
+
+    if selected.is_empty() {
+        println!();
+        return Ok(());
+    }
+
+    let required_types: Vec<&str> = selected.iter().map(|&i| RESOURCE_TYPES[i]).collect();
+
+    let rule = format!("Keep machines with {}", required_types.join(", "));
+    let message = format!("Removing machines without {}", required_types.join(", "));
+    keep_machines(&rule, &message, |machine| {
+        required_types.iter().all(|resource_type| {
+            machine
+                .resources
+                .iter()
+                .any(|resource| resource.type_ == *resource_type)
+        })
+    })
+}
+
+/**
+ * Keep only the machines using any of the selected control types.
+ */
+fn keep_machines_with_control_types() -> Result<(), Box<dyn Error>> {
+    show_section("Keep machines with control types");
+
+    let mut control_types: Vec<String> = {
+        let machines = MACHINES.lock().unwrap();
+        let machines_details = MACHINE_DETAILS.lock().unwrap();
+        get_control_types_list(&machines, &machines_details)
+            .into_keys()
+            .collect()
+    };
+
+    if control_types.is_empty() {
+        let message = format!(
+            "Error: {}",
+            "No controls information loaded, please read the data first."
+        );
+        println_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    control_types.sort();
+
+    let selected = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Control types (space to select, enter to confirm)")
+        .items(&control_types)
+        .interact()
+        .unwrap();
+
+    if selected.is_empty() {
+        println!();
+        return Ok(());
+    }
+
+    let selected_types: Vec<&str> = selected
+        .iter()
+        .map(|&i| control_types[i].as_str())
+        .collect();
+
+    // Names of the machines using any of the selected control types
+    let matching_machines: HashSet<String> = MACHINE_DETAILS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, details)| {
+            get_control_types(Some(details))
+                .iter()
+                .any(|type_| selected_types.contains(&type_.as_str()))
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    let rule = format!("Keep machines with {} controls", selected_types.join(", "));
+    let message = format!(
+        "Removing machines without {} controls",
+        selected_types.join(", ")
+    );
+    keep_machines(&rule, &message, |machine| {
+        matching_machines.contains(&machine.name)
+    })
+}
+
+/**
+ * Keep only the machines whose main display has the selected orientation.
+ */
+fn filter_by_orientation() -> Result<(), Box<dyn Error>> {
+    show_section("Filter by screen orientation");
+
+    if !has_displays_loaded() {
+        return Ok(());
+    }
+
+    let orientations = &["Horizontal", "Vertical"];
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Orientation to keep")
+        .default(0)
+        .items(&orientations[..])
+        .interact()
+        .unwrap();
+    let orientation = orientations[selection];
+
+    // Names of the machines with the other orientation, machines without displays are kept
+    let other_machines: HashSet<String> = MACHINE_DETAILS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, details)| {
+            get_orientation(Some(details)).is_some_and(|value| value != orientation)
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    let rule = format!("Keep {} machines", orientation.to_lowercase());
+    let message = format!(
+        "Removing machines without a {} screen",
+        orientation.to_lowercase()
+    );
+    keep_machines(&rule, &message, |machine| {
+        !other_machines.contains(&machine.name)
+    })
+}
+
+/**
+ * Check if the displays information of the MAME XML is loaded, showing an error if it is not.
+ */
+fn has_displays_loaded() -> bool {
+    if MACHINE_DETAILS.lock().unwrap().is_empty() {
+        let message = format!(
+            "Error: {}",
+            "No displays information loaded, please read the MAME XML first."
+        );
+        println_message(&message, ERROR);
+        println!();
+        return false;
+    }
+    true
+}
+
+/**
+ * Remove the machines with any display exceeding the resolution given.
+ */
+fn remove_machines_exceeding_resolution() -> Result<(), Box<dyn Error>> {
+    show_section("Remove machines exceeding a resolution");
+
+    if !has_displays_loaded() {
+        return Ok(());
+    }
+
+    let max_width: u32 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Maximum width in pixels")
+        .default(512)
+        .interact_text()?;
+    let max_height: u32 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Maximum height in pixels")
+        .default(288)
+        .interact_text()?;
+
+    // Displays without resolution (vector, screenless) are not taken into account
+    let exceeding_machines: HashSet<String> = MACHINE_DETAILS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, details)| {
+            details.displays.iter().any(|display| {
+                display.width.is_some_and(|width| width > max_width)
+                    || display.height.is_some_and(|height| height > max_height)
+            })
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    let rule = format!("Remove machines exceeding {}x{}", max_width, max_height);
+    let message = format!(
+        "Removing machines with a resolution higher than {}x{}",
+        max_width, max_height
+    );
+    keep_machines(&rule, &message, |machine| {
+        !exceeding_machines.contains(&machine.name)
+    })
+}
+
+/**
+ * Keep only the machines whose displays refresh within the range given.
+ */
+fn filter_by_refresh_rate() -> Result<(), Box<dyn Error>> {
+    show_section("Filter by refresh rate");
+
+    if !has_displays_loaded() {
+        return Ok(());
+    }
+
+    let min_refresh: f64 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Minimum refresh rate in Hz")
+        .default(49.0)
+        .interact_text()?;
+    let max_refresh: f64 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Maximum refresh rate in Hz")
+        .default(61.0)
+        .interact_text()?;
+
+    // Displays without refresh rate are not taken into account
+    let out_of_range_machines: HashSet<String> = MACHINE_DETAILS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, details)| {
+            details.displays.iter().any(|display| {
+                display
+                    .refresh
+                    .is_some_and(|refresh| refresh < min_refresh || refresh > max_refresh)
+            })
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    let rule = format!(
+        "Keep machines refreshing between {} and {} Hz",
+        min_refresh, max_refresh
+    );
+    let message = format!(
+        "Removing machines refreshing outside {} - {} Hz",
+        min_refresh, max_refresh
+    );
+    keep_machines(&rule, &message, |machine| {
+        !out_of_range_machines.contains(&machine.name)
+    })
+}
+
+/**
+ * Keep only the machines supporting the number of players given.
+ */
+fn filter_by_players() -> Result<(), Box<dyn Error>> {
+    show_section("Filter by number of players");
+
+    let min_players: u32 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Minimum number of players")
+        .default(2)
+        .interact_text()?;
+
+    let modes = &["Any mode", "Simultaneous", "Co-op", "Versus"];
+    let mode = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Game mode")
+        .default(0)
+        .items(&modes[..])
+        .interact()
+        .unwrap();
+
+    let rule = match mode {
+        0 => format!("Keep machines for {} or more players", min_players),
+        _ => format!(
+            "Keep {} machines for {} or more players",
+            modes[mode].to_lowercase(),
+            min_players
+        ),
+    };
+    let message = format!("Removing machines for less than {} players", min_players);
+    // Machines without a recognized player count are removed
+    keep_machines(&rule, &message, |machine| {
+        get_player_count(machine).is_some_and(|player_count| match mode {
+            0 => player_count.max_players >= min_players,
+            1 => player_count.max_simultaneous >= min_players,
+            2 => player_count.coop && player_count.max_simultaneous >= min_players,
+            3 => player_count.versus && player_count.max_simultaneous >= min_players,
+            _ => unreachable!(),
+        })
+    })
+}
+
+/**
+ * Remove the machines with any of the selected emulation issues.
+ */
+fn remove_machines_with_feature_issues() -> Result<(), Box<dyn Error>> {
+    show_section("Remove machines with emulation issues");
+
+    let mut issues: Vec<(String, usize)> = {
+        let machines = MACHINES.lock().unwrap();
+        let machines_details = MACHINE_DETAILS.lock().unwrap();
+        get_feature_issues_list(&machines, &machines_details)
+            .into_iter()
+            .collect()
+    };
+
+    if issues.is_empty() {
+        let message = format!(
+            "Error: {}",
+            "No emulation issues found, please read the data first."
+        );
+        println_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    issues.sort();
+
+    let items: Vec<String> = issues
+        .iter()
+        .map(|(issue, count)| format!("{} ({} machines)", issue, count))
+        .collect();
+    let selected = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Emulation issues (space to select, enter to confirm)")
+        .items(&items)
+        .interact()
+        .unwrap();
+
+    if selected.is_empty() {
+        println!();
+        return Ok(());
+    }
+
+    let selected_issues: Vec<&str> = selected.iter().map(|&i| issues[i].0.as_str()).collect();
+
+    // Names of the machines with any of the selected issues
+    let affected_machines: HashSet<String> = MACHINE_DETAILS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, details)| {
+            get_feature_issues(Some(details))
+                .iter()
+                .any(|issue| selected_issues.contains(&issue.as_str()))
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    let rule = format!("Remove machines with {}", selected_issues.join(", "));
+    let message = format!("Removing machines with {}", selected_issues.join(", "));
+    keep_machines(&rule, &message, |machine| {
+        !affected_machines.contains(&machine.name)
+    })
+}
+
+/**
+ * Remove the machines whose ROMs exceed the total size given.
+ */
+fn remove_machines_exceeding_rom_size() -> Result<(), Box<dyn Error>> {
+    show_section("Remove machines exceeding a ROM size");
+
+    let max_size: f64 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Maximum total ROM size in MB")
+        .default(64.0)
+        .validate_with(|size: &f64| -> Result<(), &str> {
+            if size.is_finite() && *size > 0.0 {
+                Ok(())
+            } else {
+                Err("The size must be a number greater than 0")
+            }
+        })
+        .interact_text()?;
+    let max_bytes = (max_size * 1024.0 * 1024.0) as u64;
+
+    let rule = format!("Remove machines exceeding {} MB of ROMs", max_size);
+    let message = format!("Removing machines with more than {} MB of ROMs", max_size);
+    keep_machines(&rule, &message, |machine| {
+        get_total_rom_size(machine) <= max_bytes
+    })
+}
+
+/**
+ * Keep or remove the machines whose name, description, manufacturer or source file match a pattern.
+ */
+fn filter_by_text_pattern() -> Result<(), Box<dyn Error>> {
+    show_section("Filter by text pattern");
+
+    let fields = &["Name", "Description", "Manufacturer", "Source file"];
+    let field = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Field")
+        .default(0)
+        .items(&fields[..])
+        .interact()
+        .unwrap();
+
+    let pattern_types = &["Glob (* and ? wildcards)", "Regular expression"];
+    let is_glob = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Pattern type")
+        .default(0)
+        .items(&pattern_types[..])
+        .interact()
+        .unwrap()
+        == 0;
+
+    let pattern: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Pattern")
+        .interact_text()?;
+
+    let case_sensitive = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("Case sensitive?")
+        .default(false)
+        .interact()?;
+
+    let expression = if is_glob {
+        glob_to_regex(&pattern)
+    } else {
+        pattern.clone()
+    };
+
+    let regex = match RegexBuilder::new(&expression)
+        .case_insensitive(!case_sensitive)
+        .build()
+    {
+        Ok(regex) => regex,
+        Err(err) => {
+            let message = format!("Error: {}", err);
+            println_message(&message, ERROR);
+            println!();
+            return Ok(());
+        }
+    };
+
+    let matches_pattern = |machine: &Machine| -> bool {
+        let value = match field {
+            0 => Some(machine.name.as_str()),
+            1 => machine.description.as_deref(),
+            2 => machine.manufacturer.as_deref(),
+            3 => machine.source_file.as_deref(),
+            _ => unreachable!(),
+        };
+        value.is_some_and(|value| regex.is_match(value))
+    };
+
+    {
+        let machines = MACHINES.lock().unwrap();
+        let matching_machines = machines.values().filter(|m| matches_pattern(m)).count();
+        println!(
+            "{} of {} machines match {} on {}",
+            matching_machines.to_formatted_string(&Locale::en),
+            machines.len().to_formatted_string(&Locale::en),
+            pattern,
+            fields[field].to_lowercase()
+        );
+    }
+
+    let actions = &[
+        "Keep matching machines",
+        "Remove matching machines",
+        "Cancel",
+    ];
+    let action = Select::with_theme(&ColorfulTheme::default())
+        .default(0)
+        .items(&actions[..])
+        .interact()
+        .unwrap();
+
+    let pattern_description = format!(
+        "{} {} {}",
+        fields[field].to_lowercase(),
+        if is_glob {
+            "matching glob"
+        } else {
+            "matching regex"
+        },
+        pattern
+    );
+
+    match action {
+        0 => {
+            let rule = format!("Keep machines with {}", pattern_description);
+            let message = format!("Removing machines not matching {}", pattern);
+            keep_machines(&rule, &message, matches_pattern)
+        }
+        1 => {
+            let rule = format!("Remove machines with {}", pattern_description);
+            let message = format!("Removing machines matching {}", pattern);
+            keep_machines(&rule, &message, |machine| !matches_pattern(machine))
+        }
+        _ => {
+            println!();
+            Ok(())
+        }
+    }
+}
+
+/**
+ * Convert a glob pattern into an anchored regular expression.
+ */
+fn glob_to_regex(glob: &str) -> String {
+    let mut expression = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => expression.push_str(".*"),
+            '?' => expression.push('.'),
+            _ => expression.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    expression.push('$');
+    expression
+}
+
+/**
+ * Keep only the machines matching the predicate.
+ * The removed machines are recorded in the filter log with the given rule.
+ */
+pub fn keep_machines<F>(rule: &str, message: &str, predicate: F) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&Machine) -> bool,
+{
+    println_message(message, WRITE);
+
+    let time = std::time::Instant::now();
+    {
+        let mut machines_guard = MACHINES.lock().unwrap();
+
+        if machines_guard.is_empty() {
+            let message = format!(
+                "Error: {}",
+                "No machines data loaded, please read the data first."
+            );
+            print_message(&message, ERROR);
+            println!();
+            return Ok(());
+        }
+
+        let (kept_machines, removed_machines): (
+            HashMap<String, Machine>,
+            HashMap<String, Machine>,
+        ) = machines_guard
+            .drain()
+            .partition(|(_, machine)| predicate(machine));
+
+        let total_removed = removed_machines.len();
+        record_removed_machines(removed_machines.into_values().collect(), rule);
+        *machines_guard = kept_machines;
+
+        let rounded_secs = (time.elapsed().as_secs_f32() * 10.0).round() / 10.0;
+        let message = format!("{} machines removed in {}s", total_removed, rounded_secs);
+        print_message(&message, SUCCESS);
+        println!();
+    }
+
+    Ok(())
+}
+
+/**
+ * Replace the current machines with the filtered ones.
+ * The machines left out are recorded in the filter log with the given rule.
+ */
+fn replace_machines(
+    machines: &mut HashMap<String, Machine>,
+    filtered_machines: HashMap<String, Machine>,
+    rule: &str,
+) -> usize {
+    let removed_machines: Vec<Machine> = machines
+        .drain()
+        .filter(|(name, _)| !filtered_machines.contains_key(name))
+        .map(|(_, machine)| machine)
+        .collect();
+    let total_removed = removed_machines.len();
+
+    record_removed_machines(removed_machines, rule);
+    *machines = filtered_machines;
+
+    total_removed
+}
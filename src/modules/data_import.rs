@@ -1,9 +1,12 @@
-use crate::helpers::ui_helper::show_section;
-use crate::MACHINES;
+use crate::helpers::details_reader::{find_mame_xml_file, read_machine_details};
+use crate::helpers::ui_helper::{icons::*, print_message, println_message, show_section};
+use crate::modules::data_diff::save_snapshot;
+use crate::{FILTER_LOG, FLAG_REQUIRED_MACHINES, MACHINES, MACHINE_DETAILS};
 use dialoguer::{theme::ColorfulTheme, Select};
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use mame_parser::models::MameDataType;
+use mame_parser::models::{Machine, MameDataType};
 use mame_parser::progress::{CallbackType, ProgressInfo, SharedProgressCallback};
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::thread;
 use std::{error::Error, path::Path};
@@ -13,7 +16,13 @@ use std::{error::Error, path::Path};
  */
 pub fn show_import_submenu() -> Result<(), Box<dyn Error>> {
     loop {
-        let selections = &["Download files", "Unpack files", "Read files", "< Back"];
+        let selections = &[
+            "Download files",
+            "Unpack files",
+            "Read files",
+            "Save snapshot",
+            "< Back",
+        ];
         let selection = Select::with_theme(&ColorfulTheme::default())
             .default(0)
             .items(&selections[..])
@@ -24,7 +33,8 @@ pub fn show_import_submenu() -> Result<(), Box<dyn Error>> {
             0 => download_all_files()?,
             1 => unpack_all_files()?,
             2 => read_all_files()?,
-            3 => {
+            3 => save_snapshot()?,
+            4 => {
                 break;
             }
             _ => unreachable!(),
@@ -50,7 +60,7 @@ fn download_all_files() -> Result<(), Box<dyn Error>> {
                  let progress_bar = multi_progress.add(ProgressBar::new(100));
                  progress_bar.set_style(
                      ProgressStyle::default_bar()
-                         .template(&format!("{{spinner:.green}} [{{elapsed_precise}}] [{{bar:20.cyan/blue}}] {{bytes}}/{{total_bytes}} ({{eta}}) {{msg}}"))
+                         .template("{spinner:.green} [{elapsed_precise}] [{bar:20.cyan/blue}] {bytes}/{total_bytes} ({eta}) {msg}")
                          .progress_chars("#>-"),
                  );
                  (data_type, progress_bar)
@@ -90,7 +100,7 @@ fn download_all_files() -> Result<(), Box<dyn Error>> {
     // Wait for all threads to finish
     multi_progress.join().unwrap();
 
-    // Print the result
+    // Show the files that failed to download
     for handle in handles {
         match handle.join().unwrap() {
             Ok(_) => {
@@ -122,7 +132,7 @@ fn unpack_all_files() -> Result<(), Box<dyn Error>> {
                   let progress_bar = multi_progress.add(ProgressBar::new(100));
                   progress_bar.set_style(
                       ProgressStyle::default_bar()
-                          .template(&format!("{{spinner:.green}} [{{elapsed_precise}}] [{{bar:20.cyan/blue}}] {{pos}}/{{len}} ({{eta}}) {{msg}}"))
+                          .template("{spinner:.green} [{elapsed_precise}] [{bar:20.cyan/blue}] {pos}/{len} ({eta}) {msg}")
                           .progress_chars("#>-"),
                   );
                   (data_type, progress_bar)
@@ -162,7 +172,7 @@ fn unpack_all_files() -> Result<(), Box<dyn Error>> {
     // Wait for all threads to finish
     multi_progress.join().unwrap();
 
-    // Print the result
+    // Show the files that failed to unpack
     for handle in handles {
         match handle.join().unwrap() {
             Ok(_) => {
@@ -184,6 +194,32 @@ fn read_all_files() -> Result<(), Box<dyn Error>> {
     // Define the workspace path
     let workspace_path = Path::new("data");
 
+    // Replace the current data set with the machines read
+    match read_workspace(workspace_path) {
+        Ok(machines) => {
+            let mut machines_guard = MACHINES.lock().unwrap();
+            *machines_guard = machines;
+            // A new data set starts with an empty filter log
+            FILTER_LOG.lock().unwrap().clear();
+            *FLAG_REQUIRED_MACHINES.lock().unwrap() = false;
+            drop(machines_guard);
+
+            read_all_machine_details(workspace_path);
+        }
+        Err(e) => {
+            eprintln!("Error reading data files: {}", e);
+        }
+    }
+
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Read the extracted files of a workspace showing the progress of each data type.
+ */
+pub fn read_workspace(workspace_path: &Path) -> Result<HashMap<String, Machine>, Box<dyn Error>> {
     // Create a multi progress bar
     let multi_progress: Arc<MultiProgress> = Arc::new(MultiProgress::new());
 
@@ -195,7 +231,7 @@ fn read_all_files() -> Result<(), Box<dyn Error>> {
                   let progress_bar = multi_progress.add(ProgressBar::new(100));
                   progress_bar.set_style(
                       ProgressStyle::default_bar()
-                          .template(&format!("{{spinner:.green}} [{{elapsed_precise}}] [{{bar:20.cyan/blue}}] {{pos}}/{{len}} ({{eta}}) {{msg}}"))
+                          .template("{spinner:.green} [{elapsed_precise}] [{bar:20.cyan/blue}] {pos}/{len} ({eta}) {msg}")
                           .progress_chars("#>-"),
                   );
                   (data_type, progress_bar)
@@ -238,18 +274,44 @@ fn read_all_files() -> Result<(), Box<dyn Error>> {
 
     handle.join().unwrap();
 
-    // Print the result
-    match machines {
-        Ok(machines) => {
-            let mut machines_guard = MACHINES.lock().unwrap();
-            *machines_guard = machines;
-        }
-        Err(e) => {
-            eprintln!("Error reading data files: {}", e);
+    machines.map_err(|err| err.to_string().into())
+}
+
+/**
+ * Read the controls, displays and hardware details of the machines from the MAME XML.
+ */
+fn read_all_machine_details(workspace_path: &Path) {
+    let mut details_guard = MACHINE_DETAILS.lock().unwrap();
+    details_guard.clear();
+
+    let file_path = match find_mame_xml_file(workspace_path) {
+        Ok(file_path) => file_path,
+        Err(err) => {
+            let message = format!("Error: {}", err);
+            println_message(&message, ERROR);
+            return;
         }
-    }
+    };
 
-    println!();
+    let message = "Reading machine details";
+    println_message(message, WRITE);
 
-    Ok(())
+    let time = std::time::Instant::now();
+
+    match read_machine_details(&file_path) {
+        Ok(machines_details) => {
+            let rounded_secs = (time.elapsed().as_secs_f32() * 10.0).round() / 10.0;
+            let message = format!(
+                "Details read for {} machines in {}s",
+                machines_details.len(),
+                rounded_secs
+            );
+            *details_guard = machines_details;
+            print_message(&message, SUCCESS);
+        }
+        Err(err) => {
+            let message = format!("Error reading machine details: {}", err);
+            print_message(&message, ERROR);
+        }
+    }
 }
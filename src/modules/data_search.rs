@@ -0,0 +1,691 @@
+use crate::helpers::dependency_helper::get_dependencies;
+use crate::helpers::machine_helper::format_flag;
+use crate::helpers::search_helper::{fuzzy_search, FuzzyQuery, MIN_FUZZY_SCORE};
+use crate::helpers::ui_helper::{icons::*, println_message, show_section};
+use crate::{FILTER_LOG, MACHINES};
+use dialoguer::{console::style, theme::ColorfulTheme, Input, Select};
+use mame_parser::models::Machine;
+use num_format::{Locale, ToFormattedString};
+use prettytable::{row, Cell, Row, Table};
+use std::collections::BTreeMap;
+use std::error::Error;
+
+/**
+ * Maximum number of search results that can be selected.
+ */
+const MAX_SEARCH_RESULTS: usize = 100;
+
+/**
+ * Machine of a series, either in the current data or removed by a filter.
+ */
+struct SeriesEntry {
+    name: String,
+    description: String,
+    year: String,
+    manufacturer: String,
+    clone_of: Option<String>,
+    /**
+     * Rule of the filter that removed the machine, if it was removed.
+     */
+    removed_by: Option<String>,
+}
+
+/**
+ * Show the search submenu.
+ */
+pub fn show_search_submenu() -> Result<(), Box<dyn Error>> {
+    loop {
+        let selections = &[
+            "Search by name",
+            "Search by description",
+            "Fuzzy search",
+            "Browse series",
+            "< Back",
+        ];
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .default(0)
+            .items(&selections[..])
+            .interact()
+            .unwrap();
+
+        match selection {
+            0 => search_machines(false)?,
+            1 => search_machines(true)?,
+            2 => fuzzy_search_machines()?,
+            3 => browse_series()?,
+            4 => {
+                break;
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    Ok(())
+}
+
+/**
+ * Search the current machines by name or description and show the details of the one selected.
+ */
+fn search_machines(by_description: bool) -> Result<(), Box<dyn Error>> {
+    if by_description {
+        show_section("Search by description");
+    } else {
+        show_section("Search by name");
+    }
+
+    let results: Vec<(String, String)> = {
+        let machines = MACHINES.lock().unwrap();
+
+        if machines.is_empty() {
+            let message = format!(
+                "Error: {}",
+                "No machines data loaded, please read the data first."
+            );
+            println_message(&message, ERROR);
+            println!();
+            return Ok(());
+        }
+
+        let text: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Search text")
+            .interact_text()?;
+        let text = text.trim().to_lowercase();
+
+        let mut results: Vec<(String, String)> = machines
+            .values()
+            .filter(|machine| {
+                if by_description {
+                    machine
+                        .description
+                        .as_ref()
+                        .is_some_and(|description| description.to_lowercase().contains(&text))
+                } else {
+                    machine.name.to_lowercase().contains(&text)
+                }
+            })
+            .map(|machine| {
+                let description = machine.description.clone().unwrap_or_default();
+                let label = format!("{} - {}", machine.name, description);
+                (machine.name.clone(), label)
+            })
+            .collect();
+        results.sort();
+
+        results
+    };
+
+    select_search_result(&results)?;
+
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Search the current machines allowing typos, ranking them by how well their description,
+ * name or normalized name match.
+ */
+fn fuzzy_search_machines() -> Result<(), Box<dyn Error>> {
+    show_section("Fuzzy search");
+
+    let results: Vec<(String, String)> = {
+        let machines = MACHINES.lock().unwrap();
+
+        if machines.is_empty() {
+            let message = format!(
+                "Error: {}",
+                "No machines data loaded, please read the data first."
+            );
+            println_message(&message, ERROR);
+            println!();
+            return Ok(());
+        }
+
+        let text: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Search text")
+            .interact_text()?;
+
+        let mut query = FuzzyQuery::new(&text);
+        if query.is_empty() {
+            let message = format!("Error: {}", "The search text has no letters or digits.");
+            println_message(&message, ERROR);
+            println!();
+            return Ok(());
+        }
+
+        let time = std::time::Instant::now();
+
+        let results = fuzzy_search(&machines, &mut query, MIN_FUZZY_SCORE);
+
+        let rounded_secs = (time.elapsed().as_secs_f32() * 100.0).round() / 100.0;
+        let message = format!(
+            "{} machines searched in {}s",
+            machines.len().to_formatted_string(&Locale::en),
+            rounded_secs
+        );
+        println_message(&message, WRITE);
+
+        results
+            .into_iter()
+            .map(|(name, score)| {
+                let description = machines[&name].description.clone().unwrap_or_default();
+                let label = format!("{} - {} ({:.0}%)", name, description, score);
+                (name, label)
+            })
+            .collect()
+    };
+
+    select_search_result(&results)?;
+
+    println!();
+
+    Ok(())
+}
+
+/**
+ * List every series with how many of its machines survive the filters, and show the machines
+ * of the series selected sorted by year.
+ */
+fn browse_series() -> Result<(), Box<dyn Error>> {
+    show_section("Browse series");
+
+    let mut series: BTreeMap<String, Vec<SeriesEntry>> = BTreeMap::new();
+    {
+        let machines = MACHINES.lock().unwrap();
+
+        if machines.is_empty() {
+            let message = format!(
+                "Error: {}",
+                "No machines data loaded, please read the data first."
+            );
+            println_message(&message, ERROR);
+            println!();
+            return Ok(());
+        }
+
+        let filter_log = FILTER_LOG.lock().unwrap();
+
+        let kept = machines.values().map(|machine| (machine, None));
+        let removed = filter_log
+            .iter()
+            .map(|entry| (&entry.machine, Some(entry.rule.clone())));
+        for (machine, removed_by) in kept.chain(removed) {
+            if let Some(name) = &machine.series {
+                series.entry(name.clone()).or_default().push(SeriesEntry {
+                    name: machine.name.clone(),
+                    description: machine.description.clone().unwrap_or_default(),
+                    year: machine.year.clone().unwrap_or_default(),
+                    manufacturer: machine.manufacturer.clone().unwrap_or_default(),
+                    clone_of: machine.clone_of.clone(),
+                    removed_by,
+                });
+            }
+        }
+    }
+
+    for entries in series.values_mut() {
+        entries.sort_by(|a, b| a.year.cmp(&b.year).then(a.name.cmp(&b.name)));
+    }
+
+    if series.is_empty() {
+        let message = format!("Error: {}", "No series information in the machines.");
+        println_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let complete = series
+        .values()
+        .filter(|entries| entries.iter().all(|entry| entry.removed_by.is_none()))
+        .count();
+    let message = format!(
+        "{} series, {} complete after the filters",
+        series.len().to_formatted_string(&Locale::en),
+        complete.to_formatted_string(&Locale::en)
+    );
+    println_message(&message, SUCCESS);
+
+    let names: Vec<&String> = series.keys().collect();
+    let mut selections: Vec<String> = series
+        .iter()
+        .map(|(name, entries)| {
+            let kept = entries
+                .iter()
+                .filter(|entry| entry.removed_by.is_none())
+                .count();
+            format!(
+                "{} ({}/{}, {:.0}%)",
+                name,
+                kept,
+                entries.len(),
+                kept as f64 * 100.0 / entries.len() as f64
+            )
+        })
+        .collect();
+    selections.push("< Back".to_string());
+
+    loop {
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Series")
+            .default(0)
+            .items(&selections[..])
+            .interact()
+            .unwrap();
+
+        if selection == names.len() {
+            break;
+        }
+
+        show_series(names[selection], &series[names[selection]]);
+    }
+
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Show the machines of a series with the parents and clones marked.
+ */
+fn show_series(name: &str, entries: &[SeriesEntry]) {
+    let kept = entries
+        .iter()
+        .filter(|entry| entry.removed_by.is_none())
+        .count();
+
+    let mut table = Table::new();
+    table.set_titles(Row::new(vec![Cell::new(&format!(
+        "{} ({} of {} machines kept)",
+        name,
+        kept,
+        entries.len()
+    ))
+    .style_spec("H6cFg")]));
+
+    table.add_row(row![b -> "Year", "Machine", "Description", "Manufacturer", "Type", "Status"]);
+    for entry in entries {
+        let type_ = match &entry.clone_of {
+            Some(parent) => format!("Clone of {}", parent),
+            None => "Parent".to_string(),
+        };
+        let status = match &entry.removed_by {
+            Some(rule) => format!("Removed: {}", rule),
+            None => "Kept".to_string(),
+        };
+        table.add_row(row![
+            entry.year,
+            entry.name,
+            entry.description,
+            entry.manufacturer,
+            type_,
+            status
+        ]);
+    }
+
+    table.printstd();
+}
+
+/**
+ * Let the user pick one of the search results, given as name and label, and show its details.
+ */
+fn select_search_result(results: &[(String, String)]) -> Result<(), Box<dyn Error>> {
+    if results.is_empty() {
+        let message = format!("Error: {}", "No machines found.");
+        println_message(&message, ERROR);
+        return Ok(());
+    }
+
+    let message = format!(
+        "{} machines found",
+        results.len().to_formatted_string(&Locale::en)
+    );
+    println_message(&message, SUCCESS);
+    if results.len() > MAX_SEARCH_RESULTS {
+        let message = format!(
+            "Showing the first {}, refine the search to see the rest",
+            MAX_SEARCH_RESULTS
+        );
+        println_message(&message, WRITE);
+    }
+
+    let mut selections: Vec<&str> = results
+        .iter()
+        .take(MAX_SEARCH_RESULTS)
+        .map(|(_, label)| label.as_str())
+        .collect();
+    selections.push("< Back");
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Machine")
+        .default(0)
+        .items(&selections[..])
+        .interact()
+        .unwrap();
+
+    if selection < selections.len() - 1 {
+        show_machine_details(&results[selection].0)?;
+    }
+
+    Ok(())
+}
+
+/**
+ * Show every field of a machine, allowing to move to its parent and clones.
+ */
+pub fn show_machine_details(name: &str) -> Result<(), Box<dyn Error>> {
+    let mut current = name.to_string();
+
+    loop {
+        let (machine, has_parent, clones) = {
+            let machines = MACHINES.lock().unwrap();
+
+            let machine = match machines.get(&current) {
+                Some(machine) => machine.clone(),
+                None => {
+                    let message = format!("Error: Machine {} not found.", current);
+                    println_message(&message, ERROR);
+                    return Ok(());
+                }
+            };
+            let has_parent = machine
+                .clone_of
+                .as_ref()
+                .is_some_and(|parent| machines.contains_key(parent));
+            let mut clones: Vec<String> = machines
+                .values()
+                .filter(|clone| clone.clone_of.as_ref() == Some(&machine.name))
+                .map(|clone| clone.name.clone())
+                .collect();
+            clones.sort();
+
+            (machine, has_parent, clones)
+        };
+
+        show_section(&format!("Machine {}", machine.name));
+        print_machine_details(&machine, &clones);
+
+        let mut selections: Vec<String> = Vec::new();
+        let parent = machine.clone_of.clone().filter(|_| has_parent);
+        if let Some(parent) = &parent {
+            selections.push(format!("Go to parent {}", parent));
+        }
+        if !clones.is_empty() {
+            selections.push(format!("Go to a clone ({})", clones.len()));
+        }
+        if machine.clone_of.is_some() || !clones.is_empty() {
+            selections.push("Show family tree".to_string());
+        }
+        if !machine.history_sections.is_empty() {
+            selections.push("Show history".to_string());
+        }
+        selections.push("< Back".to_string());
+
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .default(0)
+            .items(&selections[..])
+            .interact()
+            .unwrap();
+
+        let selected = selections[selection].as_str();
+        if selected.starts_with("Go to parent") {
+            current = parent.unwrap();
+        } else if selected.starts_with("Go to a clone") {
+            let clone = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt("Clone")
+                .default(0)
+                .items(&clones[..])
+                .interact()
+                .unwrap();
+            current = clones[clone].clone();
+        } else if selected == "Show family tree" {
+            let root = machine.clone_of.as_ref().unwrap_or(&machine.name);
+            show_family_tree(root);
+        } else if selected == "Show history" {
+            print_history(&machine);
+        } else {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/**
+ * Show a parent machine and all its clones as a tree.
+ */
+pub fn show_family_tree(parent: &str) {
+    let machines = MACHINES.lock().unwrap();
+
+    let mut clones: Vec<&Machine> = machines
+        .values()
+        .filter(|machine| machine.clone_of.as_deref() == Some(parent))
+        .collect();
+    clones.sort_by(|a, b| a.year.cmp(&b.year).then(a.name.cmp(&b.name)));
+
+    let mut table = Table::new();
+    table.set_titles(Row::new(vec![Cell::new(&format!(
+        "Family of {} ({} clones)",
+        parent,
+        clones.len()
+    ))
+    .style_spec("H8cFg")]));
+
+    table.add_row(row![b -> "Machine", "Description", "Year", "Manufacturer", "Driver status", "Languages", "BIOS", "Devices"]);
+    match machines.get(parent) {
+        Some(machine) => table.add_row(get_family_row(machine, "")),
+        None => table.add_row(row![parent, "(not in current data)"]),
+    };
+    for (index, clone) in clones.iter().enumerate() {
+        let prefix = if index == clones.len() - 1 {
+            "└─ "
+        } else {
+            "├─ "
+        };
+        table.add_row(get_family_row(clone, prefix));
+    }
+
+    table.printstd();
+}
+
+/**
+ * Get the tree row of a family member, flagging the BIOS and devices it depends on.
+ */
+fn get_family_row(machine: &Machine, prefix: &str) -> Row {
+    let dependencies = get_dependencies(machine);
+    let bios = dependencies
+        .iter()
+        .find(|(_, kind)| *kind == "BIOS")
+        .map(|(name, _)| name.to_string())
+        .unwrap_or_default();
+    let devices = dependencies
+        .iter()
+        .filter(|(_, kind)| *kind == "device")
+        .count();
+
+    row![
+        format!("{}{}", prefix, machine.name),
+        machine.description.clone().unwrap_or_default(),
+        machine.year.clone().unwrap_or_default(),
+        machine.manufacturer.clone().unwrap_or_default(),
+        machine.driver_status.clone().unwrap_or_default(),
+        machine.languages.join(", "),
+        bios,
+        r -> devices
+    ]
+}
+
+/**
+ * Print the fields of a machine, with a table for each list of items.
+ */
+fn print_machine_details(machine: &Machine, clones: &[String]) {
+    let mut table = Table::new();
+    table.set_titles(Row::new(vec![Cell::new(&machine.name).style_spec("H2cFg")]));
+
+    let extended_data = machine.extended_data.clone().unwrap_or_default();
+    let category = match (&machine.category, &machine.subcategory) {
+        (Some(category), Some(subcategory)) => format!("{} - {}", category, subcategory),
+        (Some(category), None) => category.clone(),
+        _ => String::new(),
+    };
+
+    let fields = [
+        (
+            "Description",
+            machine.description.clone().unwrap_or_default(),
+        ),
+        ("Normalized name", extended_data.name.unwrap_or_default()),
+        ("Year", machine.year.clone().unwrap_or_default()),
+        (
+            "Manufacturer",
+            machine.manufacturer.clone().unwrap_or_default(),
+        ),
+        (
+            "Source file",
+            machine.source_file.clone().unwrap_or_default(),
+        ),
+        ("Parent", machine.clone_of.clone().unwrap_or_default()),
+        ("ROM of", machine.rom_of.clone().unwrap_or_default()),
+        ("Sample of", machine.sample_of.clone().unwrap_or_default()),
+        ("Clones", clones.join(", ")),
+        ("Category", category),
+        ("Series", machine.series.clone().unwrap_or_default()),
+        ("Players", machine.players.clone().unwrap_or_default()),
+        ("Languages", machine.languages.join(", ")),
+        (
+            "Driver status",
+            machine.driver_status.clone().unwrap_or_default(),
+        ),
+        (
+            "BIOS",
+            format_flag(machine.is_bios).unwrap_or_default().to_string(),
+        ),
+        (
+            "Device",
+            format_flag(machine.is_device)
+                .unwrap_or_default()
+                .to_string(),
+        ),
+        (
+            "Mechanical",
+            format_flag(machine.is_mechanical)
+                .unwrap_or_default()
+                .to_string(),
+        ),
+        (
+            "Runnable",
+            format_flag(machine.runnable)
+                .unwrap_or_default()
+                .to_string(),
+        ),
+        (
+            "Mature",
+            format_flag(machine.is_mature)
+                .unwrap_or_default()
+                .to_string(),
+        ),
+        (
+            "Device refs",
+            join_names(machine.device_refs.iter().map(|device| &device.name)),
+        ),
+        (
+            "Samples",
+            join_names(machine.samples.iter().map(|sample| &sample.name)),
+        ),
+        (
+            "Software lists",
+            join_names(machine.software_list.iter().map(|software| &software.name)),
+        ),
+        (
+            "History sections",
+            join_names(machine.history_sections.iter().map(|section| &section.name)),
+        ),
+    ];
+    for (field, value) in fields {
+        if !value.is_empty() {
+            table.add_row(row![b -> field, value]);
+        }
+    }
+    table.printstd();
+
+    if !machine.bios_sets.is_empty() {
+        let mut table = Table::new();
+        table.set_titles(Row::new(vec![Cell::new("BIOS sets").style_spec("H2cFg")]));
+        table.add_row(row![b -> "Name", "Description"]);
+        for bios_set in &machine.bios_sets {
+            table.add_row(row![bios_set.name, bios_set.description]);
+        }
+        table.printstd();
+    }
+
+    if !machine.roms.is_empty() {
+        let mut table = Table::new();
+        table.set_titles(Row::new(vec![Cell::new("ROMs").style_spec("H6cFg")]));
+        table.add_row(row![b -> "Name", "Size", "CRC", "SHA1", "Merge", "Status"]);
+        for rom in &machine.roms {
+            table.add_row(row![
+                rom.name,
+                r -> rom.size.to_formatted_string(&Locale::en),
+                rom.crc.clone().unwrap_or_default(),
+                rom.sha1.clone().unwrap_or_default(),
+                rom.merge.clone().unwrap_or_default(),
+                rom.status.clone().unwrap_or_default()
+            ]);
+        }
+        table.printstd();
+    }
+
+    if !machine.disks.is_empty() {
+        let mut table = Table::new();
+        table.set_titles(Row::new(vec![Cell::new("Disks").style_spec("H5cFg")]));
+        table.add_row(row![b -> "Name", "SHA1", "Merge", "Status", "Region"]);
+        for disk in &machine.disks {
+            table.add_row(row![
+                disk.name,
+                disk.sha1.clone().unwrap_or_default(),
+                disk.merge.clone().unwrap_or_default(),
+                disk.status.clone().unwrap_or_default(),
+                disk.region.clone().unwrap_or_default()
+            ]);
+        }
+        table.printstd();
+    }
+
+    if !machine.resources.is_empty() {
+        let mut table = Table::new();
+        table.set_titles(Row::new(vec![Cell::new("Resources").style_spec("H3cFg")]));
+        table.add_row(row![b -> "Type", "Name", "Size"]);
+        for resource in &machine.resources {
+            table.add_row(row![
+                resource.type_,
+                resource.name,
+                r -> resource.size.to_formatted_string(&Locale::en)
+            ]);
+        }
+        table.printstd();
+    }
+}
+
+/**
+ * Print the history sections of a machine in order.
+ */
+fn print_history(machine: &Machine) {
+    let mut sections: Vec<_> = machine.history_sections.iter().collect();
+    sections.sort_by_key(|section| section.order);
+
+    for section in sections {
+        println!();
+        println!("{}", style(&section.name).bold().green());
+        println!("{}", section.text.trim());
+    }
+    println!();
+}
+
+/**
+ * Join the names of the related machines, samples or lists shown in the machine details.
+ */
+fn join_names<'a>(names: impl Iterator<Item = &'a String>) -> String {
+    names
+        .map(|name| name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -1,16 +1,31 @@
-use crate::helpers::ui_helper::icons::ERROR;
-use crate::helpers::ui_helper::{println_message, show_section};
-use crate::MACHINES;
-use dialoguer::{theme::ColorfulTheme, Select};
+use crate::helpers::details_helper::{
+    get_control_types_list, get_cpus_list, get_feature_issues_list, get_orientation,
+};
+use crate::helpers::graph_helper::{get_dependency_chains, get_reference_counts};
+use crate::helpers::group_helper::{get_field_label, group_by, pivot, Pivot, GROUP_FIELDS};
+use crate::helpers::machine_helper::{get_total_rom_size, requires_disks, requires_samples};
+use crate::helpers::players_helper::{get_player_count, get_unrecognized_players_list};
+use crate::helpers::report_helper::{print_stats_table, write_report, REPORT_FORMATS};
+use crate::helpers::ui_helper::{icons::*, print_message, println_message, show_section};
+use crate::models::machine_details::MachineDetails;
+use crate::models::stats_table::StatsTable;
+use crate::modules::data_diff::{compare_data_sets, detect_renamed_machines};
+use crate::modules::data_search::show_family_tree;
+use crate::{MACHINES, MACHINE_DETAILS};
+use dialoguer::{console::style, theme::ColorfulTheme, Input, Select};
 use mame_parser::models::collections::{
     get_categories_list, get_languages_list, get_manufacturers_list, get_players_list,
     get_series_list, get_subcategories_list,
 };
 use mame_parser::models::Machine;
-use num_format::{Locale, ToFormattedString};
-use prettytable::{row, Cell, Row, Table};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
+use std::path::Path;
+
+/**
+ * Number of rows shown on each page of the top tables.
+ */
+const PAGE_SIZE: usize = 25;
 
 /**
  * Show the filter submenu.
@@ -19,12 +34,25 @@ pub fn show_stats_submenu() -> Result<(), Box<dyn Error>> {
     loop {
         let selections = &[
             "General stats",
-            "Top 10 categories",
-            "Top 10 subcategories",
-            "Top 10 manufacturers",
-            "Top 10 series",
-            "Top 10 languages",
-            "Top 10 players information",
+            "Top categories",
+            "Top subcategories",
+            "Top manufacturers",
+            "Top series",
+            "Top languages",
+            "Top players information",
+            "ROM size ranges",
+            "Top control types",
+            "Top CPUs",
+            "Screen orientations",
+            "Player counts",
+            "Top emulation issues",
+            "Group by fields",
+            "Cross-tab report",
+            "Largest families",
+            "Dependency graph stats",
+            "Compare data sets",
+            "Detect renamed machines",
+            "Export full report",
             "< Back",
         ];
         let selection = Select::with_theme(&ColorfulTheme::default())
@@ -41,6 +69,7 @@ pub fn show_stats_submenu() -> Result<(), Box<dyn Error>> {
                     "Top categories".to_string(),
                     "Category".to_string(),
                     &get_categories_list(&machines),
+                    machines.len(),
                 )?
             }
             2 => {
@@ -49,6 +78,7 @@ pub fn show_stats_submenu() -> Result<(), Box<dyn Error>> {
                     "Top subcategories".to_string(),
                     "Category - Subcategory".to_string(),
                     &get_subcategories_list(&machines),
+                    machines.len(),
                 )?
             }
             3 => {
@@ -57,6 +87,7 @@ pub fn show_stats_submenu() -> Result<(), Box<dyn Error>> {
                     "Top manufacturers".to_string(),
                     "Manufacturer".to_string(),
                     &get_manufacturers_list(&machines),
+                    machines.len(),
                 )?
             }
             4 => {
@@ -65,6 +96,7 @@ pub fn show_stats_submenu() -> Result<(), Box<dyn Error>> {
                     "Top series".to_string(),
                     "Series".to_string(),
                     &get_series_list(&machines),
+                    machines.len(),
                 )?
             }
             5 => {
@@ -73,6 +105,7 @@ pub fn show_stats_submenu() -> Result<(), Box<dyn Error>> {
                     "Top languages".to_string(),
                     "Language".to_string(),
                     &get_languages_list(&machines),
+                    machines.len(),
                 )?
             }
             6 => {
@@ -81,9 +114,50 @@ pub fn show_stats_submenu() -> Result<(), Box<dyn Error>> {
                     "Top players information".to_string(),
                     "Player".to_string(),
                     &get_players_list(&machines),
+                    machines.len(),
                 )?
             }
-            7 => {
+            7 => show_rom_size_ranges()?,
+            8 => {
+                let machines = MACHINES.lock().unwrap();
+                let machines_details = MACHINE_DETAILS.lock().unwrap();
+                show_top_by_collection(
+                    "Top control types".to_string(),
+                    "Control type".to_string(),
+                    &get_control_types_list(&machines, &machines_details),
+                    machines.len(),
+                )?
+            }
+            9 => {
+                let machines = MACHINES.lock().unwrap();
+                let machines_details = MACHINE_DETAILS.lock().unwrap();
+                show_top_by_collection(
+                    "Top CPUs".to_string(),
+                    "CPU".to_string(),
+                    &get_cpus_list(&machines, &machines_details),
+                    machines.len(),
+                )?
+            }
+            10 => show_screen_orientations()?,
+            11 => show_player_counts()?,
+            12 => {
+                let machines = MACHINES.lock().unwrap();
+                let machines_details = MACHINE_DETAILS.lock().unwrap();
+                show_top_by_collection(
+                    "Top emulation issues".to_string(),
+                    "Feature status".to_string(),
+                    &get_feature_issues_list(&machines, &machines_details),
+                    machines.len(),
+                )?
+            }
+            13 => show_group_by()?,
+            14 => show_cross_tab()?,
+            15 => show_largest_families()?,
+            16 => show_dependency_stats()?,
+            17 => compare_data_sets()?,
+            18 => detect_renamed_machines()?,
+            19 => export_full_report()?,
+            20 => {
                 break;
             }
             _ => unreachable!(),
@@ -111,48 +185,594 @@ fn show_stats() -> Result<(), Box<dyn Error>> {
         return Ok(());
     }
 
+    let machines_details = MACHINE_DETAILS.lock().unwrap();
+
+    let table = get_general_stats_table(&machines, &machines_details);
+    print_stats_table(&table);
+
+    offer_export("general_stats", "General stats", &[table]);
+
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Get the table with the general statistics.
+ */
+fn get_general_stats_table(
+    machines: &HashMap<String, Machine>,
+    machines_details: &HashMap<String, MachineDetails>,
+) -> StatsTable {
     let machines_vec = machines.values().collect::<Vec<&Machine>>();
 
     let total_machines = machines_vec.len();
     let total_clones = machines_vec.iter().filter(|m| m.clone_of.is_some()).count();
     let total_originals = total_machines - total_clones;
 
-    let total_manufacturers = get_manufacturers_list(&machines).len();
-    let total_categories = get_categories_list(&machines).len();
-    let total_subcategories = get_subcategories_list(&machines).len();
-    let total_series = get_series_list(&machines).len();
-    let total_languages = get_languages_list(&machines).len();
-    let total_players = get_players_list(&machines).len();
+    let total_manufacturers = get_manufacturers_list(machines).len();
+    let total_categories = get_categories_list(machines).len();
+    let total_subcategories = get_subcategories_list(machines).len();
+    let total_series = get_series_list(machines).len();
+    let total_languages = get_languages_list(machines).len();
+    let total_players = get_players_list(machines).len();
 
     let total_machines_with_history = machines_vec
         .iter()
-        .filter(|m| m.history_sections.len() > 0)
+        .filter(|m| !m.history_sections.is_empty())
         .count();
 
     let total_machines_with_resources = machines_vec
         .iter()
-        .filter(|m| m.resources.len() > 0)
+        .filter(|m| !m.resources.is_empty())
+        .count();
+
+    let total_machines_requiring_disks = machines_vec.iter().filter(|m| requires_disks(m)).count();
+    let total_machines_requiring_samples =
+        machines_vec.iter().filter(|m| requires_samples(m)).count();
+
+    let total_machines_with_controls = machines_vec
+        .iter()
+        .filter(|m| {
+            machines_details
+                .get(&m.name)
+                .is_some_and(|details| details.input.is_some())
+        })
+        .count();
+
+    let total_machines_with_displays = machines_vec
+        .iter()
+        .filter(|m| {
+            machines_details
+                .get(&m.name)
+                .is_some_and(|details| !details.displays.is_empty())
+        })
         .count();
 
-    let mut table = Table::new();
-    table.set_titles(Row::new(vec![
-        Cell::new("MAME information statistics").style_spec("H2cFg")
-    ]));
-
-    table.add_row(row![b -> "Information", "Amount"]);
-    table.add_row(row!["Machines", r -> total_machines.to_formatted_string(&Locale::en)]);
-    table.add_row(row!["Originals", r -> total_originals.to_formatted_string(&Locale::en)]);
-    table.add_row(row!["Clones", r -> total_clones.to_formatted_string(&Locale::en)]);
-    table.add_row(row!["Manufacturers", r -> total_manufacturers.to_formatted_string(&Locale::en)]);
-    table.add_row(row!["Categories", r -> total_categories.to_formatted_string(&Locale::en)]);
-    table.add_row(row!["Subcategories", r -> total_subcategories.to_formatted_string(&Locale::en)]);
-    table.add_row(row!["Series", r -> total_series.to_formatted_string(&Locale::en)]);
-    table.add_row(row!["Languages", r -> total_languages.to_formatted_string(&Locale::en)]);
-    table.add_row(row!["Players information", r -> total_players.to_formatted_string(&Locale::en)]);
-    table.add_row(row!["Machines with history", r -> total_machines_with_history.to_formatted_string(&Locale::en)]);
-    table.add_row(row!["Machines with resources", r -> total_machines_with_resources.to_formatted_string(&Locale::en)]);
-
-    table.printstd();
+    let values = [
+        ("Machines", total_machines),
+        ("Originals", total_originals),
+        ("Clones", total_clones),
+        ("Manufacturers", total_manufacturers),
+        ("Categories", total_categories),
+        ("Subcategories", total_subcategories),
+        ("Series", total_series),
+        ("Languages", total_languages),
+        ("Players information", total_players),
+        ("Machines with history", total_machines_with_history),
+        ("Machines with resources", total_machines_with_resources),
+        ("Machines requiring disks", total_machines_requiring_disks),
+        (
+            "Machines requiring samples",
+            total_machines_requiring_samples,
+        ),
+        (
+            "Machines with controls information",
+            total_machines_with_controls,
+        ),
+        (
+            "Machines with display information",
+            total_machines_with_displays,
+        ),
+    ];
+
+    StatsTable {
+        title: "MAME information statistics".to_string(),
+        headers: get_headers(&["Information", "Amount"]),
+        rows: values
+            .iter()
+            .map(|(information, amount)| vec![information.to_string(), amount.to_string()])
+            .collect(),
+        totals: None,
+        numeric_columns: vec![1],
+    }
+}
+
+/**
+ * Show how many machines fall into each total ROM size range.
+ */
+fn show_rom_size_ranges() -> Result<(), Box<dyn Error>> {
+    show_section("ROM size ranges");
+
+    let machines = MACHINES.lock().unwrap();
+
+    if machines.is_empty() {
+        let message = format!(
+            "Error: {}",
+            "No machines data loaded, please read the data first."
+        );
+        println_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let table = get_rom_size_ranges_table(&machines);
+    print_stats_table(&table);
+
+    offer_export("rom_size_ranges", "ROM size ranges", &[table]);
+
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Get the table with the number of machines in each total ROM size range.
+ */
+fn get_rom_size_ranges_table(machines: &HashMap<String, Machine>) -> StatsTable {
+    // Upper limit of each range in MB
+    let ranges: [(&str, u64); 6] = [
+        ("Up to 1 MB", 1),
+        ("1 MB - 8 MB", 8),
+        ("8 MB - 32 MB", 32),
+        ("32 MB - 128 MB", 128),
+        ("128 MB - 512 MB", 512),
+        ("More than 512 MB", u64::MAX),
+    ];
+    let mut counts = [0usize; 6];
+
+    for machine in machines.values() {
+        let size = get_total_rom_size(machine);
+        let position = ranges
+            .iter()
+            .position(|(_, limit)| size <= limit.saturating_mul(1024 * 1024))
+            .unwrap_or(ranges.len() - 1);
+        counts[position] += 1;
+    }
+
+    StatsTable {
+        title: "ROM size ranges".to_string(),
+        headers: get_headers(&["Total ROM size", "Machines"]),
+        rows: ranges
+            .iter()
+            .zip(counts.iter())
+            .map(|((range, _), count)| vec![range.to_string(), count.to_string()])
+            .collect(),
+        totals: None,
+        numeric_columns: vec![1],
+    }
+}
+
+/**
+ * Show how many machines have a horizontal or vertical screen.
+ */
+fn show_screen_orientations() -> Result<(), Box<dyn Error>> {
+    show_section("Screen orientations");
+
+    let machines = MACHINES.lock().unwrap();
+
+    if machines.is_empty() {
+        let message = format!(
+            "Error: {}",
+            "No machines data loaded, please read the data first."
+        );
+        println_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let machines_details = MACHINE_DETAILS.lock().unwrap();
+
+    let table = get_screen_orientations_table(&machines, &machines_details);
+    print_stats_table(&table);
+
+    offer_export("screen_orientations", "Screen orientations", &[table]);
+
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Get the table with the number of machines for each screen orientation.
+ */
+fn get_screen_orientations_table(
+    machines: &HashMap<String, Machine>,
+    machines_details: &HashMap<String, MachineDetails>,
+) -> StatsTable {
+    let orientations = ["Horizontal", "Vertical", "No display"];
+    let mut counts = [0usize; 3];
+
+    for name in machines.keys() {
+        let position = match get_orientation(machines_details.get(name)) {
+            Some("Horizontal") => 0,
+            Some(_) => 1,
+            None => 2,
+        };
+        counts[position] += 1;
+    }
+
+    StatsTable {
+        title: "Screen orientations".to_string(),
+        headers: get_headers(&["Orientation", "Machines", "%"]),
+        rows: orientations
+            .iter()
+            .zip(counts.iter())
+            .map(|(orientation, count)| {
+                let percentage = *count as f64 * 100.0 / machines.len() as f64;
+                vec![
+                    orientation.to_string(),
+                    count.to_string(),
+                    format!("{:.1}", percentage),
+                ]
+            })
+            .collect(),
+        totals: None,
+        numeric_columns: vec![1, 2],
+    }
+}
+
+/**
+ * Show how many machines support each number of players, and the players values not understood.
+ */
+fn show_player_counts() -> Result<(), Box<dyn Error>> {
+    show_section("Player counts");
+
+    let machines = MACHINES.lock().unwrap();
+
+    if machines.is_empty() {
+        let message = format!(
+            "Error: {}",
+            "No machines data loaded, please read the data first."
+        );
+        println_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let tables = get_player_counts_tables(&machines);
+    for table in &tables {
+        print_stats_table(table);
+    }
+
+    offer_export("player_counts", "Player counts", &tables);
+
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Get the table with the number of machines by maximum number of players, followed by the
+ * table of the players values not understood when there are any.
+ */
+fn get_player_counts_tables(machines: &HashMap<String, Machine>) -> Vec<StatsTable> {
+    // Machines, simultaneous, alternating, co-op and versus by maximum number of players
+    let mut counts: BTreeMap<u32, [usize; 5]> = BTreeMap::new();
+    for machine in machines.values() {
+        if let Some(player_count) = get_player_count(machine) {
+            let count = counts.entry(player_count.max_players).or_insert([0; 5]);
+            count[0] += 1;
+            count[1] += player_count.simultaneous as usize;
+            count[2] += player_count.alternating as usize;
+            count[3] += player_count.coop as usize;
+            count[4] += player_count.versus as usize;
+        }
+    }
+
+    let mut tables = vec![StatsTable {
+        title: "Player counts".to_string(),
+        headers: get_headers(&[
+            "Max players",
+            "Machines",
+            "Simultaneous",
+            "Alternating",
+            "Co-op",
+            "Versus",
+        ]),
+        rows: counts
+            .iter()
+            .map(|(max_players, count)| {
+                let mut row = vec![max_players.to_string()];
+                row.extend(count.iter().map(|value| value.to_string()));
+                row
+            })
+            .collect(),
+        totals: None,
+        numeric_columns: vec![0, 1, 2, 3, 4, 5],
+    }];
+
+    let unrecognized = get_unrecognized_players_list(machines);
+    if !unrecognized.is_empty() {
+        let mut values: Vec<(&String, &usize)> = unrecognized.iter().collect();
+        values.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+
+        tables.push(StatsTable {
+            title: "Unrecognized players information".to_string(),
+            headers: get_headers(&["Players", "Machines"]),
+            rows: values
+                .into_iter()
+                .map(|(players, count)| vec![players.clone(), count.to_string()])
+                .collect(),
+            totals: None,
+            numeric_columns: vec![1],
+        });
+    }
+
+    tables
+}
+
+/**
+ * Count the machines by the values of one field, or of a combination of two fields.
+ */
+fn show_group_by() -> Result<(), Box<dyn Error>> {
+    let theme = ColorfulTheme::default();
+
+    let labels: Vec<&str> = GROUP_FIELDS.iter().map(|(_, label)| *label).collect();
+    let first = Select::with_theme(&theme)
+        .with_prompt("Group by")
+        .default(0)
+        .items(&labels[..])
+        .interact()
+        .unwrap();
+
+    let mut second_labels = vec!["(none)"];
+    second_labels.extend(
+        labels
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != first)
+            .map(|(_, label)| *label),
+    );
+    let second = Select::with_theme(&theme)
+        .with_prompt("Then by")
+        .default(0)
+        .items(&second_labels[..])
+        .interact()
+        .unwrap();
+
+    let mut fields = vec![GROUP_FIELDS[first].0];
+    if second > 0 {
+        let (field, _) = GROUP_FIELDS
+            .iter()
+            .find(|(_, label)| *label == second_labels[second])
+            .unwrap();
+        fields.push(field);
+    }
+
+    let column = fields
+        .iter()
+        .map(|field| get_field_label(field))
+        .collect::<Vec<_>>()
+        .join(" / ");
+
+    let machines = MACHINES.lock().unwrap();
+    show_top_by_collection(
+        format!("Machines by {}", column.to_lowercase()),
+        column,
+        &group_by(&machines, &fields),
+        machines.len(),
+    )
+}
+
+/**
+ * Show a two-dimensional table counting the machines by the values of two fields,
+ * with row and column totals and an optional export of the full table.
+ */
+fn show_cross_tab() -> Result<(), Box<dyn Error>> {
+    show_section("Cross-tab report");
+
+    let machines = MACHINES.lock().unwrap();
+
+    if machines.is_empty() {
+        let message = format!(
+            "Error: {}",
+            "No machines data loaded, please read the data first."
+        );
+        println_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let theme = ColorfulTheme::default();
+
+    let labels: Vec<&str> = GROUP_FIELDS.iter().map(|(_, label)| *label).collect();
+    let row = Select::with_theme(&theme)
+        .with_prompt("Rows")
+        .default(0)
+        .items(&labels[..])
+        .interact()
+        .unwrap();
+    let column = Select::with_theme(&theme)
+        .with_prompt("Columns")
+        .default(1)
+        .items(&labels[..])
+        .interact()
+        .unwrap();
+    let max_rows: usize = Input::with_theme(&theme)
+        .with_prompt("Maximum rows (0 for all)")
+        .default(20)
+        .interact_text()?;
+    let max_columns: usize = Input::with_theme(&theme)
+        .with_prompt("Maximum columns (0 for all)")
+        .default(8)
+        .interact_text()?;
+
+    let (row_field, row_label) = GROUP_FIELDS[row];
+    let (column_field, column_label) = GROUP_FIELDS[column];
+    let pivot = pivot(&machines, row_field, column_field);
+
+    let rows = limit_values(&pivot.rows, &pivot.row_totals, max_rows);
+    let columns = limit_values(&pivot.columns, &pivot.column_totals, max_columns);
+
+    let title = format!("{} by {}", row_label, column_label.to_lowercase());
+    print_stats_table(&get_cross_tab_table(
+        &title,
+        &pivot,
+        row_label,
+        column_label,
+        &rows,
+        &columns,
+    ));
+
+    if rows.len() < pivot.rows.len() || columns.len() < pivot.columns.len() {
+        let message = format!(
+            "Showing {} of {} rows and {} of {} columns, the export includes all of them",
+            rows.len(),
+            pivot.rows.len(),
+            columns.len(),
+            pivot.columns.len()
+        );
+        println_message(&message, WRITE);
+    }
+
+    let table = get_cross_tab_table(
+        &title,
+        &pivot,
+        row_label,
+        column_label,
+        &pivot.rows.iter().collect::<Vec<_>>(),
+        &pivot.columns.iter().collect::<Vec<_>>(),
+    );
+    let file_stem = format!("cross_tab_{}_by_{}", row_field, column_field);
+    offer_export(&file_stem, &title, &[table]);
+
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Keep the values with the highest totals, in their original order.
+ */
+fn limit_values<'a>(
+    values: &'a [String],
+    totals: &HashMap<String, usize>,
+    max: usize,
+) -> Vec<&'a String> {
+    if max == 0 || values.len() <= max {
+        return values.iter().collect();
+    }
+
+    let kept: Vec<String> = get_top(totals, max)
+        .into_iter()
+        .map(|(value, _)| value)
+        .collect();
+    values.iter().filter(|value| kept.contains(value)).collect()
+}
+
+/**
+ * Get the table of a cross-tab for the given rows and columns, with their totals.
+ */
+fn get_cross_tab_table(
+    title: &str,
+    pivot: &Pivot,
+    row_label: &str,
+    column_label: &str,
+    rows: &[&String],
+    columns: &[&String],
+) -> StatsTable {
+    let mut headers = vec![format!("{} \\ {}", row_label, column_label)];
+    headers.extend(columns.iter().map(|column| column.to_string()));
+    headers.push("Total".to_string());
+
+    let rows = rows
+        .iter()
+        .map(|row| {
+            let mut values = vec![row.to_string()];
+            for column in columns {
+                let count = pivot
+                    .counts
+                    .get(&(row.to_string(), column.to_string()))
+                    .copied()
+                    .unwrap_or(0);
+                values.push(count.to_string());
+            }
+            values.push(pivot.row_totals[*row].to_string());
+            values
+        })
+        .collect();
+
+    let mut totals = vec!["Total".to_string()];
+    totals.extend(
+        columns
+            .iter()
+            .map(|column| pivot.column_totals[*column].to_string()),
+    );
+    totals.push(pivot.total.to_string());
+
+    StatsTable {
+        title: title.to_string(),
+        headers,
+        rows,
+        totals: Some(totals),
+        numeric_columns: (1..columns.len() + 2).collect(),
+    }
+}
+
+/**
+ * Show the parents with the most clones, allowing to open the family tree of any of them.
+ */
+fn show_largest_families() -> Result<(), Box<dyn Error>> {
+    show_section("Largest families");
+
+    let table = {
+        let machines = MACHINES.lock().unwrap();
+
+        if machines.is_empty() {
+            let message = format!(
+                "Error: {}",
+                "No machines data loaded, please read the data first."
+            );
+            println_message(&message, ERROR);
+            println!();
+            return Ok(());
+        }
+
+        get_largest_families_table(&machines, 10)
+    };
+
+    if table.rows.is_empty() {
+        let message = format!("Error: {}", "No clones in the current machines.");
+        println_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    print_stats_table(&table);
+
+    offer_export(
+        "largest_families",
+        "Largest families",
+        std::slice::from_ref(&table),
+    );
+
+    let mut selections: Vec<String> = table
+        .rows
+        .iter()
+        .map(|row| format!("Show family of {} - {}", row[1], row[2]))
+        .collect();
+    selections.push("< Back".to_string());
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .default(0)
+        .items(&selections[..])
+        .interact()
+        .unwrap();
+
+    if selection < table.rows.len() {
+        show_family_tree(&table.rows[selection][1]);
+    }
 
     println!();
 
@@ -160,12 +780,133 @@ fn show_stats() -> Result<(), Box<dyn Error>> {
 }
 
 /**
- * Show the top by collection.
+ * Get the table with the parents with the most clones.
+ */
+fn get_largest_families_table(machines: &HashMap<String, Machine>, count: usize) -> StatsTable {
+    let mut clones: HashMap<String, usize> = HashMap::new();
+    for machine in machines.values() {
+        if let Some(parent) = &machine.clone_of {
+            *clones.entry(parent.clone()).or_insert(0) += 1;
+        }
+    }
+
+    StatsTable {
+        title: "Largest families".to_string(),
+        headers: get_headers(&["#", "Parent", "Description", "Clones"]),
+        rows: get_top(&clones, count)
+            .into_iter()
+            .enumerate()
+            .map(|(index, (parent, count))| {
+                let description = machines
+                    .get(&parent)
+                    .and_then(|machine| machine.description.clone())
+                    .unwrap_or_default();
+                vec![
+                    (index + 1).to_string(),
+                    parent,
+                    description,
+                    count.to_string(),
+                ]
+            })
+            .collect(),
+        totals: None,
+        numeric_columns: vec![0, 3],
+    }
+}
+
+/**
+ * Show the most referenced devices and BIOS, and the machines with the deepest dependency chains.
+ */
+fn show_dependency_stats() -> Result<(), Box<dyn Error>> {
+    show_section("Dependency graph stats");
+
+    let machines = MACHINES.lock().unwrap();
+
+    if machines.is_empty() {
+        let message = format!(
+            "Error: {}",
+            "No machines data loaded, please read the data first."
+        );
+        println_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let tables = get_dependency_stats_tables(&machines, 10);
+    for table in &tables {
+        print_stats_table(table);
+    }
+
+    offer_export("dependency_graph_stats", "Dependency graph stats", &tables);
+
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Get the tables with the most referenced devices and BIOS, and the deepest dependency chains.
+ */
+fn get_dependency_stats_tables(
+    machines: &HashMap<String, Machine>,
+    count: usize,
+) -> Vec<StatsTable> {
+    let (devices, bios) = get_reference_counts(machines);
+
+    let mut tables: Vec<StatsTable> = [
+        ("Most referenced devices", "Device", &devices),
+        ("Most referenced BIOS", "BIOS", &bios),
+    ]
+    .into_iter()
+    .map(|(title, column, map)| StatsTable {
+        title: title.to_string(),
+        headers: get_headers(&["#", column, "Machines"]),
+        rows: get_top(map, count)
+            .into_iter()
+            .enumerate()
+            .map(|(index, (name, count))| vec![(index + 1).to_string(), name, count.to_string()])
+            .collect(),
+        totals: None,
+        numeric_columns: vec![0, 2],
+    })
+    .collect();
+
+    let mut chains: Vec<(String, Vec<String>)> =
+        get_dependency_chains(machines).into_iter().collect();
+    chains.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(&b.0)));
+
+    tables.push(StatsTable {
+        title: "Deepest dependency chains".to_string(),
+        headers: get_headers(&["#", "Machine", "Depth", "Chain"]),
+        rows: chains
+            .iter()
+            .take(count)
+            .enumerate()
+            .map(|(index, (name, chain))| {
+                vec![
+                    (index + 1).to_string(),
+                    name.clone(),
+                    (chain.len() - 1).to_string(),
+                    chain.join(" → "),
+                ]
+            })
+            .collect(),
+        totals: None,
+        numeric_columns: vec![0, 2],
+    });
+
+    tables
+}
+
+/**
+ * Show the top by collection, asking how many entries to show and how to sort them,
+ * with the percentage of the machines in each entry and a page for every 25 entries.
  */
 fn show_top_by_collection(
     title: String,
     column: String,
     map: &HashMap<String, usize>,
+    total_machines: usize,
 ) -> Result<(), Box<dyn Error>> {
     show_section(&title);
 
@@ -179,29 +920,302 @@ fn show_top_by_collection(
         return Ok(());
     }
 
-    let top = get_top(map, 10);
+    let theme = ColorfulTheme::default();
 
-    let mut table = Table::new();
-    table.set_titles(Row::new(vec![Cell::new(&title).style_spec("H3cFg")]));
+    let count: usize = Input::with_theme(&theme)
+        .with_prompt("Number of entries (0 for all)")
+        .default(10)
+        .interact_text()?;
+    let sort_selections = &["Machines", "Name"];
+    let sort = Select::with_theme(&theme)
+        .with_prompt("Sort by")
+        .default(0)
+        .items(&sort_selections[..])
+        .interact()
+        .unwrap();
 
-    table.add_row(row![b -> "#", column, "Machines"]);
+    let count = if count == 0 { map.len() } else { count };
+    let table = get_top_table(&title, &column, map, total_machines, count, sort == 1);
 
-    let mut counter = 0;
-    for (item, count) in top {
-        counter += 1;
-        table.add_row(row![counter, item, count.to_formatted_string(&Locale::en)]);
+    let pages = table.rows.len().div_ceil(PAGE_SIZE);
+    let mut page = 0;
+
+    loop {
+        let mut page_table = table.clone();
+        if pages > 1 {
+            page_table.title = format!("{} (page {} of {})", title, page + 1, pages);
+            page_table.rows = table
+                .rows
+                .iter()
+                .skip(page * PAGE_SIZE)
+                .take(PAGE_SIZE)
+                .cloned()
+                .collect();
+        }
+
+        print_stats_table(&page_table);
+
+        if pages <= 1 {
+            break;
+        }
+
+        let mut selections = Vec::new();
+        if page + 1 < pages {
+            selections.push("Next page");
+        }
+        if page > 0 {
+            selections.push("Previous page");
+        }
+        selections.push("< Back");
+
+        let selection = Select::with_theme(&theme)
+            .default(0)
+            .items(&selections[..])
+            .interact()
+            .unwrap();
+
+        match selections[selection] {
+            "Next page" => page += 1,
+            "Previous page" => page -= 1,
+            _ => break,
+        }
     }
 
-    table.printstd();
+    offer_export(&get_file_stem(&title), &title, &[table]);
 
     println!();
 
     Ok(())
 }
 
+/**
+ * Get the table with the entries of a collection with the most machines, sorted by machines
+ * or by name, with the percentage of the machines in each entry. As a machine can be in several
+ * entries, such as several languages, the percentages can add up to more than 100.
+ */
+fn get_top_table(
+    title: &str,
+    column: &str,
+    map: &HashMap<String, usize>,
+    total_machines: usize,
+    count: usize,
+    sort_by_name: bool,
+) -> StatsTable {
+    let mut top = get_top(map, count);
+    if sort_by_name {
+        top.sort_by(|a, b| {
+            a.0.to_lowercase()
+                .cmp(&b.0.to_lowercase())
+                .then(a.0.cmp(&b.0))
+        });
+    }
+
+    StatsTable {
+        title: title.to_string(),
+        headers: get_headers(&["#", column, "Machines", "% of machines"]),
+        rows: top
+            .into_iter()
+            .enumerate()
+            .map(|(index, (item, count))| {
+                let percentage = count as f64 * 100.0 / total_machines as f64;
+                vec![
+                    (index + 1).to_string(),
+                    item,
+                    count.to_string(),
+                    format!("{:.1}", percentage),
+                ]
+            })
+            .collect(),
+        totals: None,
+        numeric_columns: vec![0, 2, 3],
+    }
+}
+
+/**
+ * Export every statistics view to a single report, with the top tables limited to the
+ * number of entries asked.
+ */
+fn export_full_report() -> Result<(), Box<dyn Error>> {
+    show_section("Export full report");
+
+    let machines = MACHINES.lock().unwrap();
+
+    if machines.is_empty() {
+        let message = format!(
+            "Error: {}",
+            "No machines data loaded, please read the data first."
+        );
+        println_message(&message, ERROR);
+        println!();
+        return Ok(());
+    }
+
+    let machines_details = MACHINE_DETAILS.lock().unwrap();
+
+    let theme = ColorfulTheme::default();
+
+    let labels: Vec<&str> = REPORT_FORMATS.iter().map(|(label, _)| *label).collect();
+    let format = Select::with_theme(&theme)
+        .with_prompt("Format")
+        .default(0)
+        .items(&labels[..])
+        .interact()
+        .unwrap();
+    let count: usize = Input::with_theme(&theme)
+        .with_prompt("Number of entries of the top tables (0 for all)")
+        .default(10)
+        .interact_text()?;
+    let count = if count == 0 { usize::MAX } else { count };
+
+    let mut tables = vec![get_general_stats_table(&machines, &machines_details)];
+    for (title, column, map) in [
+        ("Top categories", "Category", get_categories_list(&machines)),
+        (
+            "Top subcategories",
+            "Category - Subcategory",
+            get_subcategories_list(&machines),
+        ),
+        (
+            "Top manufacturers",
+            "Manufacturer",
+            get_manufacturers_list(&machines),
+        ),
+        ("Top series", "Series", get_series_list(&machines)),
+        ("Top languages", "Language", get_languages_list(&machines)),
+        (
+            "Top players information",
+            "Player",
+            get_players_list(&machines),
+        ),
+    ] {
+        tables.push(get_top_table(
+            title,
+            column,
+            &map,
+            machines.len(),
+            count,
+            false,
+        ));
+    }
+    tables.push(get_rom_size_ranges_table(&machines));
+    for (title, column, map) in [
+        (
+            "Top control types",
+            "Control type",
+            get_control_types_list(&machines, &machines_details),
+        ),
+        (
+            "Top CPUs",
+            "CPU",
+            get_cpus_list(&machines, &machines_details),
+        ),
+    ] {
+        tables.push(get_top_table(
+            title,
+            column,
+            &map,
+            machines.len(),
+            count,
+            false,
+        ));
+    }
+    tables.push(get_screen_orientations_table(&machines, &machines_details));
+    tables.extend(get_player_counts_tables(&machines));
+    tables.push(get_top_table(
+        "Top emulation issues",
+        "Feature status",
+        &get_feature_issues_list(&machines, &machines_details),
+        machines.len(),
+        count,
+        false,
+    ));
+    tables.push(get_largest_families_table(&machines, count));
+    tables.extend(get_dependency_stats_tables(&machines, count));
+
+    let (_, extension) = REPORT_FORMATS[format];
+    let file_name = format!(
+        "stats_report_{}.{}",
+        chrono::Local::now().format("%Y%m%d"),
+        extension
+    );
+    let report_path = Path::new("data").join("reports").join(file_name);
+
+    save_report(&report_path, "MAME statistics report", &tables);
+
+    println!();
+
+    Ok(())
+}
+
+/**
+ * Ask whether to export the tables of a view, and in which format, saving them to
+ * data/reports/<file_stem>.<extension>.
+ */
+fn offer_export(file_stem: &str, title: &str, tables: &[StatsTable]) {
+    let mut selections = vec!["Don't export"];
+    selections.extend(REPORT_FORMATS.iter().map(|(label, _)| *label));
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Export this view")
+        .default(0)
+        .items(&selections[..])
+        .interact()
+        .unwrap();
+
+    if selection == 0 {
+        return;
+    }
+
+    let (_, extension) = REPORT_FORMATS[selection - 1];
+    let report_path = Path::new("data")
+        .join("reports")
+        .join(format!("{}.{}", file_stem, extension));
+
+    save_report(&report_path, title, tables);
+}
+
+/**
+ * Write a report and show where it was written, or the error if it could not be written.
+ */
+fn save_report(report_path: &Path, title: &str, tables: &[StatsTable]) {
+    match write_report(report_path, title, tables) {
+        Ok(()) => {
+            let message = format!("Report written to {}", style(report_path.display()).cyan());
+            print_message(&message, SUCCESS);
+        }
+        Err(err) => {
+            let message = format!("Error: {}", err);
+            print_message(&message, ERROR);
+        }
+    }
+}
+
+/**
+ * Get the name of the report file of a view from its title, such as `top_cpus` for "Top CPUs".
+ */
+fn get_file_stem(title: &str) -> String {
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/**
+ * Get the headers of a table from their names.
+ */
+fn get_headers(headers: &[&str]) -> Vec<String> {
+    headers.iter().map(|header| header.to_string()).collect()
+}
+
+/**
+ * Get the entries with the most machines, ordering the ties by name so the result is
+ * always the same.
+ */
 fn get_top(map: &HashMap<String, usize>, count: usize) -> Vec<(String, usize)> {
     let mut vec: Vec<_> = map.iter().collect();
-    vec.sort_by(|a, b| b.1.cmp(a.1));
+    vec.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
     vec.into_iter()
         .take(count)
         .map(|(k, v)| (k.clone(), *v))
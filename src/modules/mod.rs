@@ -1,4 +1,9 @@
+pub mod data_cabinets;
+pub mod data_collections;
+pub mod data_diff;
 pub mod data_export;
+pub mod data_filter_log;
 pub mod data_filtering;
 pub mod data_import;
+pub mod data_search;
 pub mod data_stats;